@@ -0,0 +1,30 @@
+fn main() {
+    #[cfg(feature = "ffi")]
+    {
+        use std::path::Path;
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=include/aoc2023.h");
+        println!("cargo:rerun-if-env-changed=AOC2023_UPDATE_HEADER");
+        let mut config = cbindgen::Config::default();
+        config.usize_is_size_t = true;
+        let generated = Path::new(&std::env::var("OUT_DIR").unwrap()).join("aoc2023.h");
+        cbindgen::Builder::new()
+            .with_config(config)
+            .with_src("src/ffi.rs")
+            .with_language(cbindgen::Language::C)
+            .with_include_guard("AOC2023_H")
+            .with_autogen_warning("/* generated from src/ffi.rs by build.rs, do not edit */")
+            .generate()
+            .unwrap()
+            .write_to_file(&generated);
+        // the committed header is what C users build against, it only gets
+        // rewritten when asked to
+        let generated = std::fs::read_to_string(generated).unwrap();
+        if std::env::var_os("AOC2023_UPDATE_HEADER").is_some() {
+            std::fs::write("include/aoc2023.h", generated).unwrap();
+        } else if std::fs::read_to_string("include/aoc2023.h").ok().as_deref() != Some(&generated) {
+            panic!("include/aoc2023.h does not match src/ffi.rs, rebuild with AOC2023_UPDATE_HEADER=1 to regenerate it");
+        }
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -0,0 +1,54 @@
+#ifndef AOC2023_H
+#define AOC2023_H
+
+/* generated from src/ffi.rs by build.rs, do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Outcome of `aoc_solve`, exactly one of answer and error is set.
+ */
+typedef struct AocResult AocResult;
+
+/**
+ * Solves one part of a day for the `len` bytes of input at `input`.
+ * Never returns NULL, release the result with `aoc_free`.
+ *
+ * # Safety
+ *
+ * `input` must point to `len` readable bytes, it may be NULL when `len` is 0.
+ */
+struct AocResult *aoc_solve(uint32_t day, uint32_t part, const uint8_t *input, size_t len);
+
+/**
+ * The answer, or NULL if solving failed. Owned by the result.
+ *
+ * # Safety
+ *
+ * `result` must come from `aoc_solve` and not have been freed.
+ */
+const char *aoc_answer(const struct AocResult *result);
+
+/**
+ * Why solving failed, or NULL if it didn't. Owned by the result.
+ *
+ * # Safety
+ *
+ * `result` must come from `aoc_solve` and not have been freed.
+ */
+const char *aoc_error(const struct AocResult *result);
+
+/**
+ * Releases a result along with its strings, NULL is ignored.
+ *
+ * # Safety
+ *
+ * `result` must come from `aoc_solve` and not have been freed already.
+ */
+void aoc_free(struct AocResult *result);
+
+#endif /* AOC2023_H */
@@ -1,4 +1,9 @@
-use std::fs::read_to_string;
+use aoc2023::aho::Automaton;
+use aoc2023::runner::{self, Runner};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
 
 static JUST_DIGITS: &[(i32, &str)] = &[
     (0, "0"),
@@ -36,24 +41,183 @@ static DIGITS_AND_STRINGS: &[(i32, &str)] = &[
     (9, "nine"),
 ];
 
+// what counts as a digit; a word can stand for several, like `twelve`
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Vocabulary {
+    words: Vec<(Vec<i32>, String)>,
+    ignore_case: bool,
+}
+
+impl Vocabulary {
+    fn word(mut self, word: &str, digits: &[i32]) -> Self {
+        assert!(!digits.is_empty() && digits.iter().all(|d| (0..10).contains(d)), "{word} needs digits");
+        self.words.push((digits.to_vec(), word.to_string()));
+        self
+    }
+
+    fn ignore_case(mut self) -> Self {
+        self.ignore_case = true;
+        self
+    }
+
+    // one `word digits` per line, `#` starts a comment, and a line saying
+    // `ignore-case` makes the whole thing case insensitive
+    fn parse(s: &str) -> Result<Self, String> {
+        let mut v = Vocabulary::default();
+        for (i, l) in s.lines().enumerate() {
+            let l = l.split('#').next().unwrap().trim();
+            if l.is_empty() {
+                continue;
+            }
+            if l == "ignore-case" {
+                v = v.ignore_case();
+                continue;
+            }
+            let (word, digits) = l
+                .rsplit_once(char::is_whitespace)
+                .ok_or(format!("line {}: expected a word and its digits", i + 1))?;
+            let digits: Vec<i32> = digits
+                .chars()
+                .map(|c| c.to_digit(10).map(|d| d as i32))
+                .collect::<Option<_>>()
+                .ok_or(format!("line {}: {digits} is not a number", i + 1))?;
+            v = v.word(word.trim(), &digits);
+        }
+        Ok(v)
+    }
+
+    fn automaton(&self) -> Automaton<Vec<i32>> {
+        if self.ignore_case {
+            let lower = self.words.iter().map(|(d, w)| (d.clone(), w.to_lowercase())).collect::<Vec<_>>();
+            Automaton::ignore_ascii_case(lower.iter().map(|(d, w)| (d.clone(), w.as_str())))
+        } else {
+            Automaton::new(self.words.iter().map(|(d, w)| (d.clone(), w.as_str())))
+        }
+    }
+
+    // the automaton only folds ascii, anything else gets lowercased up front;
+    // offsets are then into the lowercased line
+    fn fold<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        if self.ignore_case && !line.is_ascii() {
+            Cow::Owned(line.to_lowercase())
+        } else {
+            Cow::Borrowed(line)
+        }
+    }
+}
+
+impl From<&[(i32, &str)]> for Vocabulary {
+    fn from(matchers: &[(i32, &str)]) -> Self {
+        matchers.iter().fold(Vocabulary::default(), |v, (d, w)| v.word(w, &[*d]))
+    }
+}
+
+#[cfg(test)]
 #[derive(Clone, Copy)]
 enum End {
     Left,
     Right,
 }
 
+// what to do with a line that has no digit in it
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Mode {
+    Strict,
+    // such lines count as zero
+    Lenient,
+}
+
+impl FromStr for Mode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "strict" => Ok(Mode::Strict),
+            "lenient" => Ok(Mode::Lenient),
+            _ => Err(format!("no such mode: {s}")),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct NoDigits {
+    // counting from 1, like an editor does
+    line: usize,
+    text: String,
+}
+
+impl fmt::Display for NoDigits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {} has no digits: {:?}", self.line, self.text)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Calibration {
+    sum: i32,
+    skipped: usize,
+}
+
+fn calibrate(vocabulary: &Vocabulary, s: &str, mode: Mode) -> Result<Calibration, NoDigits> {
+    let ac = vocabulary.automaton();
+    let mut c = Calibration { sum: 0, skipped: 0 };
+    for (i, x) in s.lines().enumerate() {
+        match (edge_digits(&ac, &vocabulary.fold(x)), mode) {
+            (Some(d), _) => c.sum += dgts_to_int(d),
+            (None, Mode::Lenient) => c.skipped += 1,
+            (None, Mode::Strict) => {
+                return Err(NoDigits {
+                    line: i + 1,
+                    text: x.to_string(),
+                })
+            }
+        }
+    }
+    Ok(c)
+}
+
+#[cfg(test)]
+fn solve_str(vocabulary: &Vocabulary, s: &str) -> i32 {
+    calibrate(vocabulary, s, Mode::Strict).unwrap().sum
+}
+
+#[cfg(test)]
 fn solve(matchers: &[(i32, &str)], f: &str) -> i32 {
-    read_to_string(f)
-        .unwrap()
-        .lines()
-        .map(|x| edge_digits(matchers, x))
-        .map(dgts_to_int)
-        .sum()
+    solve_str(&matchers.into(), &std::fs::read_to_string(f).unwrap())
+}
+
+fn part(r: &mut Runner, n: usize, vocabulary: &Vocabulary, input: &str, mode: Mode) {
+    let mut skipped = 0;
+    r.part(n, || {
+        let c = calibrate(vocabulary, input, mode).unwrap_or_else(|e| panic!("{e}"));
+        skipped = c.skipped;
+        c.sum
+    });
+    if skipped > 0 {
+        r.note(&format!("part {n} skipped"), skipped);
+    }
+}
+
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let mode = r.param("mode", Mode::Strict);
+    part(r, 1, &JUST_DIGITS.into(), input, mode);
+    // the vocabulary itself, not a path, so the server can't be made to read files
+    let words = r.param("vocab", String::new());
+    let words = match words.as_str() {
+        "" => DIGITS_AND_STRINGS.into(),
+        words => r.step("vocabulary", || Vocabulary::parse(words).unwrap()),
+    };
+    part(r, 2, &words, input, mode);
 }
 
+// `1 [vocabulary file]`, like vocab/de; only the binary reads files, the
+// server has to be sent the words themselves
 fn main() {
-    println!("part 1: {}", solve(JUST_DIGITS, "inputs/1b"));
-    println!("part 2: {}", solve(DIGITS_AND_STRINGS, "inputs/1b"));
+    let mut params = HashMap::new();
+    if let Some(path) = std::env::args().nth(1) {
+        let words = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("can't read {path}: {e}"));
+        params.insert("vocab".to_string(), words);
+    }
+    runner::main_with("inputs/1b", params, run);
 }
 
 fn dgts_to_int(pair: (i32, i32)) -> i32 {
@@ -61,6 +225,8 @@ fn dgts_to_int(pair: (i32, i32)) -> i32 {
     tens * 10 + ones
 }
 
+// the straightforward way, kept around to check the automaton against
+#[cfg(test)]
 fn index(end: End, string: &str, matcher: (i32, &str)) -> Option<(i32, usize)> {
     let (c, sub) = matcher;
     match end {
@@ -69,6 +235,7 @@ fn index(end: End, string: &str, matcher: (i32, &str)) -> Option<(i32, usize)> {
     }
 }
 
+#[cfg(test)]
 fn select(matchers: &[(i32, &str)], string: &str, end: End) -> i32 {
     matchers
         .iter()
@@ -78,13 +245,49 @@ fn select(matchers: &[(i32, &str)], string: &str, end: End) -> i32 {
         .0
 }
 
-fn edge_digits(matchers: &[(i32, &str)], x: &str) -> (i32, i32) {
-    (select(matchers, x, End::Left), select(matchers, x, End::Right))
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Token<'a> {
+    digits: &'a [i32],
+    // in bytes
+    offset: usize,
+    text: &'a str,
+}
+
+impl Token<'_> {
+    fn end(&self) -> usize {
+        self.offset + self.text.len()
+    }
+}
+
+// every word in the line, overlapping ones too; they come in the order they
+// end, which is also the order they start unless one word contains another
+fn tokens<'a>(ac: &'a Automaton<Vec<i32>>, line: &'a str) -> impl Iterator<Item = Token<'a>> + 'a {
+    ac.find_iter(line).map(|m| Token {
+        digits: ac.value(m.pattern),
+        offset: m.start,
+        text: &line[m.start..m.end],
+    })
+}
+
+// the longer word wins when two start (or end) at the same spot, so
+// `seventeen` beats `seven`; after that the one listed first, same as `select`
+fn edge_digits(ac: &Automaton<Vec<i32>>, x: &str) -> Option<(i32, i32)> {
+    let mut first: Option<Token> = None;
+    let mut last: Option<Token> = None;
+    for t in tokens(ac, x) {
+        if first.is_none_or(|f| (t.offset, f.end()) < (f.offset, t.end())) {
+            first = Some(t);
+        }
+        if last.is_none_or(|l| (t.end(), l.offset) > (l.end(), t.offset)) {
+            last = Some(t);
+        }
+    }
+    Some((first?.digits[0], *last?.digits.last().unwrap()))
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use super::*;
 
     #[test]
     fn to_int_test() {
@@ -117,4 +320,80 @@ mod tests {
         assert_eq!(6, select(JUST_DIGITS, "onex56xxtwo", End::Right));
         assert_eq!(5, select(JUST_DIGITS, "onex56xxtwo", End::Left));
     }
+    #[test]
+    fn edge_digits_test() {
+        let ac = Vocabulary::from(DIGITS_AND_STRINGS).automaton();
+        assert_eq!(Some((8, 2)), edge_digits(&ac, "eightwo"));
+        assert_eq!(Some((2, 1)), edge_digits(&ac, "twone"));
+        assert_eq!(None, edge_digits(&ac, "xyz"));
+        for matchers in [JUST_DIGITS, DIGITS_AND_STRINGS] {
+            let ac = Vocabulary::from(matchers).automaton();
+            for l in std::fs::read_to_string("inputs/1b").unwrap().lines() {
+                let expected = (select(matchers, l, End::Left), select(matchers, l, End::Right));
+                assert_eq!(Some(expected), edge_digits(&ac, l), "{l}");
+            }
+        }
+    }
+    #[test]
+    fn vocabulary_test() {
+        let v = Vocabulary::from(DIGITS_AND_STRINGS).word("twelve", &[1, 2]).word("seventeen", &[1, 7]);
+        assert_eq!(17, solve_str(&v, "seventeen"));
+        assert_eq!(12 + 82 + 17, solve_str(&v, "twelve\nxeight2twelve\nseventeenx"));
+        assert_eq!(12, solve_str(&v.clone().ignore_case(), "TWELVE"));
+        let en = Vocabulary::parse(&std::fs::read_to_string("vocab/en").unwrap()).unwrap();
+        assert_eq!(281, solve_str(&en, &std::fs::read_to_string("inputs/1c").unwrap()));
+        let de = Vocabulary::parse(&std::fs::read_to_string("vocab/de").unwrap()).unwrap();
+        assert!(de.ignore_case);
+        assert_eq!(12 + 30 + 11, solve_str(&de, "Zwölf\ndreinull\nEINS"));
+        assert_eq!(55 + 12, solve_str(&de, "FÜNF\nZWÖLF"));
+        assert_eq!(Err("line 2: x1 is not a number".to_string()), Vocabulary::parse("one 1\ntwo x1"));
+        assert!(Vocabulary::parse("one").is_err());
+    }
+    #[test]
+    fn tokens_test() {
+        let ac = Vocabulary::from(DIGITS_AND_STRINGS).word("eighteen", &[1, 8]).automaton();
+        let found = tokens(&ac, "xeightwone7eighteen").map(|t| (t.digits, t.offset, t.text)).collect::<Vec<_>>();
+        let expected: Vec<(&[i32], usize, &str)> = vec![
+            (&[8], 1, "eight"),
+            (&[2], 5, "two"),
+            (&[1], 7, "one"),
+            (&[7], 10, "7"),
+            (&[8], 11, "eight"),
+            (&[1, 8], 11, "eighteen"),
+        ];
+        assert_eq!(expected, found);
+
+        // other rules are easy to build on top
+        let all: i32 = tokens(&ac, "two1nine").flat_map(|t| t.digits).sum();
+        assert_eq!(12, all);
+        let middle = tokens(&ac, "1abc234def5").skip(1).map(|t| t.text).collect::<Vec<_>>();
+        assert_eq!(vec!["2", "3", "4"], middle[..middle.len() - 1]);
+        let marked = tokens(&ac, "a1bthree").fold(String::new(), |s, t| s + &format!("[{}@{}]", t.text, t.offset));
+        assert_eq!("[1@1][three@3]", marked);
+    }
+    #[test]
+    fn mode_test() {
+        let v = Vocabulary::from(JUST_DIGITS);
+        let s = "a1b\nnope\n23\n\nx4";
+        let e = calibrate(&v, s, Mode::Strict).unwrap_err();
+        assert_eq!(NoDigits { line: 2, text: "nope".to_string() }, e);
+        assert_eq!("line 2 has no digits: \"nope\"", e.to_string());
+        assert_eq!(Ok(Calibration { sum: 11 + 23 + 44, skipped: 2 }), calibrate(&v, s, Mode::Lenient));
+        assert_eq!(Ok(Mode::Lenient), "lenient".parse());
+        assert!("loose".parse::<Mode>().is_err());
+
+        let mut r = Runner::new();
+        r.params.insert("mode".to_string(), "lenient".to_string());
+        run(s, &mut r);
+        assert_eq!(Some("78"), r.answer(1));
+        assert_eq!(vec![("part 1 skipped".to_string(), "2".to_string())], r.notes[..1]);
+    }
+    #[test]
+    fn vocab_param_test() {
+        let mut r = Runner::new();
+        r.only = Some(2);
+        r.params.insert("vocab".to_string(), "ignore-case\none 1\ntwo 2".to_string());
+        run("ONEtwo\nTwo3one", &mut r);
+        assert_eq!(Some("33"), r.answer(2));
+    }
 }
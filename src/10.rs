@@ -1,5 +1,6 @@
+use aoc2023::coord::{Dir, Point2, DIRECTIONS};
+use aoc2023::runner::{self, Runner};
 use itertools::Itertools;
-use std::fs::read_to_string;
 use std::iter;
 use std::ops::Index;
 
@@ -10,15 +11,7 @@ enum Tile {
     Pipe(Dir, Dir),
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-enum Dir {
-    Left,
-    Right,
-    Down,
-    Up,
-}
-
-type Coord = (usize, usize);
+type Coord = Point2<usize>;
 
 fn clockwise(x: Dir) -> Dir {
     match x {
@@ -52,13 +45,8 @@ fn is_start(t: Tile) -> bool {
     }
 }
 
-fn step(d: Dir, (x, y): Coord) -> Coord {
-    match d {
-        Dir::Left => (x - 1, y),
-        Dir::Right => (x + 1, y),
-        Dir::Down => (x, y + 1),
-        Dir::Up => (x, y - 1),
-    }
+fn step(d: Dir, c: Coord) -> Coord {
+    c.step(d).unwrap()
 }
 
 //type Map = Vec<Vec<Tile>>;
@@ -70,7 +58,7 @@ struct Map {
 
 impl Index<Coord> for Map {
     type Output = Tile;
-    fn index(&self, (x, y): Coord) -> &Tile {
+    fn index(&self, Point2 { x, y }: Coord) -> &Tile {
         &self.tiles[y][x]
     }
 }
@@ -89,17 +77,21 @@ fn parse_tile(t: char) -> Tile {
     }
 }
 
+#[cfg(test)]
 fn parse(f: &str) -> Map {
-    let tiles = read_to_string(f)
-        .unwrap()
+    parse_str(&std::fs::read_to_string(f).unwrap())
+}
+
+fn parse_str(s: &str) -> Map {
+    let tiles = s
         .lines()
         .map(|l| l.chars().map(parse_tile).collect_vec())
         .collect_vec();
-    let mut start = (0, 0);
+    let mut start = Point2::new(0, 0);
     'outer: for (y, l) in tiles.iter().enumerate() {
         for (x, t) in l.iter().enumerate() {
             if is_start(*t) {
-                start = (x, y);
+                start = Point2::new(x, y);
                 break 'outer;
             }
         }
@@ -141,11 +133,9 @@ enum Field {
     Path,
 }
 
-static DIRECTIONS: &[Dir] = &[Dir::Left, Dir::Right, Dir::Up, Dir::Down];
-
 fn fill(f: &mut Vec<Vec<Field>>, c: Coord) {
-    if f[c.1][c.0] == Field::Untouched {
-        f[c.1][c.0] = Field::Touched;
+    if f[c.y][c.x] == Field::Untouched {
+        f[c.y][c.x] = Field::Touched;
         for dir in DIRECTIONS {
             fill(f, step(*dir, c));
         }
@@ -155,7 +145,7 @@ fn fill(f: &mut Vec<Vec<Field>>, c: Coord) {
 fn part2(m: Map, d: Dir) -> usize {
     let mut x = m.tiles.iter().map(|x| x.iter().map(|_| Field::Untouched).collect_vec()).collect_vec();
     for i in create_loop(m.to_owned(), d) {
-        x[i.coord.1][i.coord.0] = Field::Path;
+        x[i.coord.y][i.coord.x] = Field::Path;
     }
     for (i, i1) in create_loop(m, d).collect_vec().iter().circular_tuple_windows() {
         let c = step(counterclockwise(i.dir), i.coord);
@@ -168,14 +158,41 @@ fn part2(m: Map, d: Dir) -> usize {
     x.into_iter().flatten().filter(|&x| x == Field::Touched).count()
 }
 
+// just the loop, drawn with box characters
+fn loop_rows(m: &Map, d: Dir) -> Vec<String> {
+    let mut rows = m.tiles.iter().map(|l| vec!['.'; l.len()]).collect_vec();
+    for w in create_loop(m.clone(), d) {
+        let Point2 { x, y } = w.coord;
+        rows[y][x] = match m[w.coord] {
+            Tile::Starting => 'S',
+            Tile::Ground => '.',
+            Tile::Pipe(a, b) => match (a, b) {
+                (Dir::Up, Dir::Down) => '│',
+                (Dir::Left, Dir::Right) => '─',
+                (Dir::Up, Dir::Right) => '└',
+                (Dir::Down, Dir::Right) => '┌',
+                (Dir::Down, Dir::Left) => '┐',
+                _ => '┘',
+            },
+        };
+    }
+    rows.into_iter().map(|l| l.into_iter().collect()).collect_vec()
+}
+
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let m = r.parse(|| parse_str(input));
+    r.frame("loop", || loop_rows(&m, Dir::Up));
+    r.part(1, || part1(m.clone(), Dir::Up));
+    r.part(2, || part2(m, Dir::Up));
+}
+
 fn main() {
-    println!("part 1: {:?}", part1(parse("inputs/10b"), Dir::Up));
-    println!("part 2: {:?}", part2(parse("inputs/10b"), Dir::Up));
+    runner::main("inputs/10b", run);
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use super::*;
     #[test]
     fn part1_test() {
         assert_eq!(8, part1(parse("inputs/10a"), Dir::Down));
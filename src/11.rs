@@ -1,3 +1,5 @@
+use aoc2023::runner::{self, Runner};
+
 fn count_distances(d: Vec<usize>, expansion: usize) -> usize {
     let mut ahead: usize = d.iter().sum();
     let mut acc: usize = 0;
@@ -10,8 +12,12 @@ fn count_distances(d: Vec<usize>, expansion: usize) -> usize {
     acc
 }
 
+#[cfg(test)]
 fn parse(f: &str) -> (Vec<usize>, Vec<usize>) {
-    let s = std::fs::read_to_string(f).unwrap();
+    parse_str(&std::fs::read_to_string(f).unwrap())
+}
+
+fn parse_str(s: &str) -> (Vec<usize>, Vec<usize>) {
     let h: Vec<usize> = s.lines().map(|x| x.chars().filter(|&x| x == '#').count()).collect();
     let v = (0..h.len())
         .map(|i| s.lines().filter(|x| x.chars().nth(i).unwrap() == '#').count())
@@ -19,19 +25,29 @@ fn parse(f: &str) -> (Vec<usize>, Vec<usize>) {
     (h, v)
 }
 
-fn solve(f: &str, expansion: usize) -> usize {
-    let (h, v) = parse(f);
+fn distances((h, v): (Vec<usize>, Vec<usize>), expansion: usize) -> usize {
     count_distances(h, expansion) + count_distances(v, expansion)
 }
 
+#[cfg(test)]
+fn solve(f: &str, expansion: usize) -> usize {
+    distances(parse(f), expansion)
+}
+
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let galaxies = r.parse(|| parse_str(input));
+    r.part(1, || distances(galaxies.clone(), 2));
+    let expansion = r.param("expansion", 1_000_000);
+    r.part(2, || distances(galaxies, expansion));
+}
+
 fn main() {
-    println!("part 1: {:?}", solve("inputs/11b", 2));
-    println!("part 2: {:?}", solve("inputs/11b", 1_000_000));
+    runner::main("inputs/11b", run);
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use super::*;
     #[test]
     fn solver_test() {
         assert_eq!(0, count_distances(vec![5], 2));
@@ -1,6 +1,6 @@
+use aoc2023::memo::Memo;
+use aoc2023::runner::{self, Runner};
 use itertools::Itertools;
-use memoize::memoize;
-use std::fs::read_to_string;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 enum Spring {
@@ -63,33 +63,55 @@ fn feasible(r: Row, offset: usize) -> bool {
     (r.springs.len() == (offset + size)) || (r.springs[offset + size] != Spring::Damaged)
 }
 
-#[memoize]
+#[cfg(test)]
 fn count_possibilities_brute_force(r: Row) -> usize {
-    if r.ecc.len() == 0 {
-        if r.springs.iter().any(|&x| x == Spring::Damaged) {
+    count_possibilities_memo(r, &mut Memo::new())
+}
+
+fn count_possibilities_memo(r: Row, memo: &mut Memo<Row, usize>) -> usize {
+    if r.ecc.is_empty() {
+        if r.springs.contains(&Spring::Damaged) {
             return 0; // impossible
         }
         // recursion end
         return 1;
     }
-    (0..=freedom(r.clone()))
+    if let Some(n) = memo.get(&r) {
+        return n;
+    }
+    let n = (0..=freedom(r.clone()))
         .map(|i| {
             if feasible(r.to_owned(), i) {
-                count_possibilities_brute_force(cut_first(r.to_owned(), i))
+                count_possibilities_memo(cut_first(r.to_owned(), i), memo)
             } else {
                 0
             }
         })
-        .sum()
+        .sum();
+    memo.insert(r, n)
+}
+
+fn count_rows(rows: impl Iterator<Item = Row>, memo: &mut Memo<Row, usize>) -> usize {
+    // rows share nothing, so there's no point keeping entries between them
+    rows.map(|r| {
+        memo.clear();
+        count_possibilities_memo(r, memo)
+    })
+    .sum()
+}
+
+fn parse_str(s: &str) -> Vec<Row> {
+    s.lines().map(parse_line).collect()
+}
+
+#[cfg(test)]
+fn part1_memo(f: &str, memo: &mut Memo<Row, usize>) -> usize {
+    count_rows(parse_str(&std::fs::read_to_string(f).unwrap()).into_iter(), memo)
 }
 
+#[cfg(test)]
 fn part1(f: &str) -> usize {
-    read_to_string(f)
-        .unwrap()
-        .lines()
-        .map(parse_line)
-        .map(count_possibilities_brute_force)
-        .sum()
+    part1_memo(f, &mut Memo::new())
 }
 
 fn unfold(r: Row) -> Row {
@@ -100,24 +122,33 @@ fn unfold(r: Row) -> Row {
     Row { ecc, springs }
 }
 
+#[cfg(test)]
+fn part2_memo(f: &str, memo: &mut Memo<Row, usize>) -> usize {
+    count_rows(parse_str(&std::fs::read_to_string(f).unwrap()).into_iter().map(unfold), memo)
+}
+
+#[cfg(test)]
 fn part2(f: &str) -> usize {
-    read_to_string(f)
-        .unwrap()
-        .lines()
-        .map(parse_line)
-        .map(unfold)
-        .map(count_possibilities_brute_force)
-        .sum()
+    part2_memo(f, &mut Memo::new())
+}
+
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let rows = r.parse(|| parse_str(input));
+    let mut memo = Memo::new();
+    r.part(1, || count_rows(rows.iter().cloned(), &mut memo));
+    r.note("part 1 cache", memo.stats());
+    let mut memo = Memo::new();
+    r.part(2, || count_rows(rows.into_iter().map(unfold), &mut memo));
+    r.note("part 2 cache", memo.stats());
 }
 
 fn main() {
-    println!("part 1: {}", part1("inputs/12b"));
-    println!("part 2: {}", part2("inputs/12b"));
+    runner::main("inputs/12b", run);
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use super::*;
     #[test]
     fn parser_test() {
         assert_eq!(
@@ -226,4 +257,19 @@ mod tests {
     fn urgh_test() {
         assert_eq!(1, count_possibilities_brute_force(unfold(parse_line("???.### 1,1,3"))));
     }
+    #[test]
+    fn part2_test() {
+        assert_eq!(525152, part2("inputs/12a"));
+    }
+    #[test]
+    fn memo_scoped_test() {
+        let mut memo = Memo::new();
+        assert_eq!(21, part1_memo("inputs/12a", &mut memo));
+        assert!(memo.stats().misses > 0);
+        // cleared before every row, only the last one is left
+        let last = parse_line("?###???????? 3,2,1");
+        let mut fresh = Memo::new();
+        count_possibilities_memo(last, &mut fresh);
+        assert_eq!(fresh.len(), memo.len());
+    }
 }
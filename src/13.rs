@@ -1,3 +1,4 @@
+use aoc2023::runner::{self, Runner};
 use itertools::Itertools;
 
 // https://stackoverflow.com/questions/64498617/how-to-transpose-a-vector-of-vectors-in-rust
@@ -17,8 +18,8 @@ fn parse_field(f: &str) -> Field {
     f.lines().map(|x| x.chars().collect_vec()).collect_vec()
 }
 
-fn parse(f: &str) -> Vec<Field> {
-    std::fs::read_to_string(f).unwrap().split("\n\n").map(parse_field).collect()
+fn parse_str(s: &str) -> Vec<Field> {
+    s.split("\n\n").map(parse_field).collect()
 }
 
 fn defects(a: &Vec<char>, b: &Vec<char>) -> usize {
@@ -47,11 +48,16 @@ fn summarize(u: Field, defects: usize) -> usize {
     100 * above_reflection(&u, defects) + above_reflection(&transpose(u), defects)
 }
 
-fn solve(f: &str, defects: usize) -> usize {
-    parse(f).into_iter().map(|x| summarize(x, defects)).sum()
+fn summarize_all(fields: Vec<Field>, defects: usize) -> usize {
+    fields.into_iter().map(|x| summarize(x, defects)).sum()
+}
+
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let fields = r.parse(|| parse_str(input));
+    r.part(1, || summarize_all(fields.clone(), 0));
+    r.part(2, || summarize_all(fields, 1));
 }
 
 fn main() {
-    println!("part1: {}", solve("inputs/13b", 0));
-    println!("part2: {}", solve("inputs/13b", 1));
+    runner::main("inputs/13b", run);
 }
@@ -1,5 +1,6 @@
+use aoc2023::cycle;
+use aoc2023::runner::{self, Runner};
 use itertools::Itertools;
-use std::{collections::HashMap, fs::read_to_string};
 
 // https://stackoverflow.com/questions/64498617/how-to-transpose-a-vector-of-vectors-in-rust
 fn transpose<T>(v: Vec<Vec<T>>) -> Vec<Vec<T>>
@@ -84,10 +85,13 @@ fn spin(m: Map) -> Map {
     right(down(left(up(m))))
 }
 
+#[cfg(test)]
 fn parse(f: &str) -> Map {
-    read_to_string(f)
-        .unwrap()
-        .lines()
+    parse_str(&std::fs::read_to_string(f).unwrap())
+}
+
+fn parse_str(s: &str) -> Map {
+    s.lines()
         .map(|l| l.chars().map(parse_tile).collect_vec())
         .collect_vec()
 }
@@ -100,47 +104,56 @@ fn calc_load(m: Map) -> usize {
     m.iter().rev().enumerate().map(|(i, x)| (i + 1) * count_stones(x)).sum()
 }
 
-fn part1(f: &str) -> usize {
-    calc_load(up(parse(f)))
+fn tilted_load(m: Map) -> usize {
+    calc_load(up(m))
 }
 
-fn speen(m: Map, n: usize) -> Map {
-    let mut a = m.clone();
-    for _ in 0..n {
-        a = spin(a);
-    }
-    a
+fn spun_load(m: Map) -> usize {
+    calc_load(cycle::jump(m, |m| spin(m.clone()), 1_000_000_000))
 }
 
+#[cfg(test)]
+fn part1(f: &str) -> usize {
+    tilted_load(parse(f))
+}
+
+#[cfg(test)]
 fn part2(f: &str) -> usize {
-    let mut visited: HashMap<Map, usize> = HashMap::new();
-    let mut a = parse(f);
-    let target = 1_000_000_000;
-    for i in 0..target {
-        match visited.get(&a) {
-            None => {
-                visited.insert(a.to_owned(), i);
-            }
-            Some(previous) => {
-                let metacycle = i - previous;
-                let remaining = target - i;
-                let rem = remaining % metacycle;
-                return calc_load(speen(a, rem));
+    spun_load(parse(f))
+}
+
+fn render(m: &Map) -> Vec<String> {
+    let c = |t: &Tile| match t {
+        Tile::Empty => '.',
+        Tile::Round => 'O',
+        Tile::Square => '#',
+    };
+    m.iter().map(|l| l.iter().map(c).collect()).collect_vec()
+}
+
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let m = r.parse(|| parse_str(input));
+    if r.frames.is_some() {
+        r.frame("start", || render(&m));
+        let mut s = m.clone();
+        for i in 1..=3 {
+            for (name, tilt) in ["north", "west", "south", "east"].into_iter().zip([up, left, down, right]) {
+                s = tilt(s);
+                r.frame(&format!("spin {i}, {name}"), || render(&s));
             }
         }
-        a = spin(a);
     }
-    panic!()
+    r.part(1, || tilted_load(m.clone()));
+    r.part(2, || spun_load(m));
 }
 
 fn main() {
-    println!("part1: {}", part1("inputs/14b"));
-    println!("part2: {}", part2("inputs/14b"));
+    runner::main("inputs/14b", run);
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use super::*;
     #[test]
     fn shove_right_test() {
         let mut a = vec![Tile::Round, Tile::Empty];
@@ -1,13 +1,12 @@
+use aoc2023::runner::{self, Runner};
 use itertools::Itertools;
 
 fn hash(s: String) -> usize {
     s.as_bytes().iter().fold(0, |acc, x| ((acc + *x as i32) * 17) % 256) as usize
 }
 
-fn read(f: &str) -> Vec<String> {
-    std::fs::read_to_string(f)
-        .unwrap()
-        .lines()
+fn read_str(s: &str) -> Vec<String> {
+    s.lines()
         .next()
         .unwrap()
         .split(",")
@@ -42,9 +41,9 @@ fn apply(b: &mut Box, op: Op) {
     }
 }
 
-fn part2(f: &str) -> usize {
+fn focusing_power(steps: Vec<String>) -> usize {
     let mut boxes: Vec<Box> = std::iter::repeat(vec![]).take(256).collect();
-    for s in read(f) {
+    for s in steps {
         let op = parse(s);
         apply(&mut boxes[hash(op.label.clone())], op);
     }
@@ -55,18 +54,23 @@ fn part2(f: &str) -> usize {
         .sum()
 }
 
-fn part1(f: &str) -> i32 {
-    read(f).into_iter().map(hash).map(|x| x as i32).sum()
+fn hash_sum(steps: Vec<String>) -> i32 {
+    steps.into_iter().map(hash).map(|x| x as i32).sum()
+}
+
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let steps = r.parse(|| read_str(input));
+    r.part(1, || hash_sum(steps.clone()));
+    r.part(2, || focusing_power(steps));
 }
 
 fn main() {
-    println!("part 1: {:?}", part1("inputs/15b"));
-    println!("part 2: {:?}", part2("inputs/15b"));
+    runner::main("inputs/15b", run);
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use super::*;
     #[test]
     fn hash_test() {
         assert_eq!(200, hash(String::from("H")));
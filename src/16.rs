@@ -1,6 +1,7 @@
+use aoc2023::coord::{Dir, Point2};
+use aoc2023::runner::{self, Runner};
 use itertools::Itertools;
 use std::collections::HashSet;
-use std::fs::read_to_string;
 use std::ops::Index;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -16,15 +17,7 @@ enum Axis {
     Vertical,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-enum Dir {
-    Left,
-    Right,
-    Down,
-    Up,
-}
-
-type Coord = (usize, usize);
+type Coord = Point2<usize>;
 
 fn clockwise(x: Dir) -> Dir {
     match x {
@@ -60,15 +53,8 @@ fn distort(d: Dir, t: Tile) -> Vec<Dir> {
     }
 }
 
-fn step(d: Dir, (x, y): Coord, m: &Map) -> Option<Coord> {
-    let (mx, my) = m.size;
-    match d {
-        Dir::Left if x > 0 => Some((x - 1, y)),
-        Dir::Right if x < (mx - 1) => Some((x + 1, y)),
-        Dir::Down if y < (my - 1) => Some((x, y + 1)),
-        Dir::Up if y > 0 => Some((x, y - 1)),
-        _ => None,
-    }
+fn step(d: Dir, c: Coord, m: &Map) -> Option<Coord> {
+    c.step_within(d, m.size)
 }
 
 //type Map = Vec<Vec<Tile>>;
@@ -80,7 +66,7 @@ struct Map {
 
 impl Index<Coord> for Map {
     type Output = Tile;
-    fn index(&self, (x, y): Coord) -> &Tile {
+    fn index(&self, Point2 { x, y }: Coord) -> &Tile {
         &self.tiles[y][x]
     }
 }
@@ -96,13 +82,17 @@ fn parse_tile(t: char) -> Tile {
     }
 }
 
+#[cfg(test)]
 fn parse(f: &str) -> Map {
-    let tiles = read_to_string(f)
-        .unwrap()
+    parse_str(&std::fs::read_to_string(f).unwrap())
+}
+
+fn parse_str(s: &str) -> Map {
+    let tiles = s
         .lines()
         .map(|l| l.chars().map(parse_tile).collect_vec())
         .collect_vec();
-    let size = (tiles[0].len(), tiles.len());
+    let size = Point2::new(tiles[0].len(), tiles.len());
     Map { tiles, size }
 }
 
@@ -119,7 +109,7 @@ fn proceed(m: &Map, w: Photon) -> Vec<Photon> {
         .collect_vec()
 }
 
-fn run(map: &Map, initial: Photon) -> HashSet<Photon> {
+fn trace(map: &Map, initial: Photon) -> HashSet<Photon> {
     let mut photons = vec![initial];
     //let mut photons = distort(initial.dir, map[(0, 0)]).map(|dir| Photon {dir, coord: initial.coord}).collect_vec();
     let mut visited: HashSet<Photon> = HashSet::new();
@@ -129,7 +119,7 @@ fn run(map: &Map, initial: Photon) -> HashSet<Photon> {
         }
         photons = photons
             .into_iter()
-            .flat_map(|x| proceed(&map, x))
+            .flat_map(|x| proceed(map, x))
             .filter(|x| !visited.contains(x))
             .collect_vec();
         if photons.len() == 0 {
@@ -139,31 +129,30 @@ fn run(map: &Map, initial: Photon) -> HashSet<Photon> {
     visited
 }
 
-fn part2(f: &str) -> usize {
-    let map = parse(f);
+fn best_energized(map: &Map) -> usize {
     let mut pending: HashSet<Photon> = vec![
-        (0..map.size.0)
+        (0..map.size.x)
             .map(|x| Photon {
                 dir: Dir::Down,
-                coord: (x, 0),
+                coord: Point2::new(x, 0),
             })
             .collect_vec(),
-        (0..map.size.0)
+        (0..map.size.x)
             .map(|x| Photon {
                 dir: Dir::Up,
-                coord: (x, map.size.1 - 1),
+                coord: Point2::new(x, map.size.y - 1),
             })
             .collect_vec(),
-        (0..map.size.1)
+        (0..map.size.y)
             .map(|y| Photon {
                 dir: Dir::Right,
-                coord: (0, y),
+                coord: Point2::new(0, y),
             })
             .collect_vec(),
-        (0..map.size.1)
+        (0..map.size.y)
             .map(|y| Photon {
                 dir: Dir::Left,
-                coord: (map.size.0 - 1, y),
+                coord: Point2::new(map.size.x - 1, y),
             })
             .collect_vec(),
     ]
@@ -171,12 +160,10 @@ fn part2(f: &str) -> usize {
     .flatten()
     .collect();
     let mut best_so_far = 0;
-    let mut ctr = 0;
     loop {
-        ctr += 1;
         match pending.iter().next() {
             Some(initial) => {
-                let res = run(&map, *initial);
+                let res = trace(map, *initial);
                 let current = count(&res);
                 if current > best_so_far {
                     best_so_far = current
@@ -194,7 +181,6 @@ fn part2(f: &str) -> usize {
             }
         }
     }
-    print!("looped {} times", ctr);
     best_so_far
 }
 
@@ -202,23 +188,52 @@ fn count(f: &HashSet<Photon>) -> usize {
     f.iter().map(|x| x.coord).unique().count()
 }
 
+fn energized(map: &Map) -> usize {
+    let initial = Photon {
+        dir: Dir::Right,
+        coord: Point2::new(0, 0),
+    };
+    count(&trace(map, initial))
+}
+
+#[cfg(test)]
 fn part1(f: &str) -> usize {
-    let map = parse(f);
+    energized(&parse(f))
+}
+
+// the input with the energized empty tiles lit up
+fn energized_rows(input: &str, map: &Map) -> Vec<String> {
     let initial = Photon {
         dir: Dir::Right,
-        coord: (0, 0),
+        coord: Point2::new(0, 0),
     };
-    count(&run(&map, initial))
+    let lit: HashSet<Coord> = trace(map, initial).iter().map(|x| x.coord).collect();
+    input
+        .lines()
+        .enumerate()
+        .map(|(y, l)| {
+            l.chars()
+                .enumerate()
+                .map(|(x, c)| if c == '.' && lit.contains(&Point2::new(x, y)) { '#' } else { c })
+                .collect()
+        })
+        .collect_vec()
+}
+
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let map = r.parse(|| parse_str(input));
+    r.frame("energized", || energized_rows(input, &map));
+    r.part(1, || energized(&map));
+    r.part(2, || best_energized(&map));
 }
 
 fn main() {
-    println!("part 1: {:?}", part1("inputs/16b"));
-    println!("part 2: {:?}", part2("inputs/16b"));
+    runner::main("inputs/16b", run);
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use super::*;
     #[test]
     fn reflection_test() {
         assert_eq!(vec![Dir::Left], distort(Dir::Left, parse_tile('.')));
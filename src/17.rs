@@ -1,17 +1,10 @@
+use aoc2023::coord::{Dir, Point2};
+use aoc2023::runner::{self, Runner};
 use itertools::Itertools;
 use std::collections::{BTreeSet, HashMap};
-use std::fs::read_to_string;
 use std::ops::Index;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
-enum Dir {
-    Left,
-    Right,
-    Down,
-    Up,
-}
-
-type Coord = (usize, usize);
+type Coord = Point2<usize>;
 
 fn clockwise(x: Dir) -> Dir {
     match x {
@@ -30,15 +23,8 @@ fn counterclockwise(x: Dir) -> Dir {
     clockwise(opposite(x))
 }
 
-fn step(d: Dir, (x, y): Coord, m: &Map) -> Option<Coord> {
-    let (mx, my) = m.size;
-    match d {
-        Dir::Left if x > 0 => Some((x - 1, y)),
-        Dir::Right if x < (mx - 1) => Some((x + 1, y)),
-        Dir::Down if y < (my - 1) => Some((x, y + 1)),
-        Dir::Up if y > 0 => Some((x, y - 1)),
-        _ => None,
-    }
+fn step(d: Dir, c: Coord, m: &Map) -> Option<Coord> {
+    c.step_within(d, m.size)
 }
 
 type Tile = usize; // TODO check impact of size on performance
@@ -50,7 +36,7 @@ struct Map {
 
 impl Index<Coord> for Map {
     type Output = Tile;
-    fn index(&self, (x, y): Coord) -> &Tile {
+    fn index(&self, Point2 { x, y }: Coord) -> &Tile {
         &self.tiles[y][x]
     }
 }
@@ -59,13 +45,17 @@ fn parse_tile(t: char) -> Tile {
     t.to_digit(10).unwrap() as Tile
 }
 
+#[cfg(test)]
 fn parse(f: &str) -> Map {
-    let tiles = read_to_string(f)
-        .unwrap()
+    parse_str(&std::fs::read_to_string(f).unwrap())
+}
+
+fn parse_str(s: &str) -> Map {
+    let tiles = s
         .lines()
         .map(|l| l.chars().map(parse_tile).collect_vec())
         .collect_vec();
-    let size = (tiles[0].len(), tiles.len());
+    let size = Point2::new(tiles[0].len(), tiles.len());
     Map { tiles, size }
 }
 
@@ -106,23 +96,24 @@ fn create(m: &Map, parent: Nav, dir: Dir, (min, max): Turning) -> Option<Nav> {
 
 fn to_end(u: Coord, m: &Map) -> usize {
     // manhattan, guaranteed to be no less then actual total temp drop
-    let (tx, ty) = m.size;
-    let (x, y) = u;
-    (tx - x) + (ty - y)
+    u.manhattan(m.size)
 }
 
 fn finished(n: Nav, m: &Map, (min, _): Turning) -> bool {
-    let (x, y) = n.coord;
-    m.size == (x + 1, y + 1) && n.tiles_straight >= min
+    m.size == n.coord + Point2::new(1, 1) && n.tiles_straight >= min
 }
 
+#[cfg(test)]
 fn solve(f: &str, t: Turning) -> usize {
-    let map = parse(f);
+    least_heat_loss(&parse(f), t)
+}
+
+fn least_heat_loss(map: &Map, t: Turning) -> usize {
     let initial = Nav {
         dir: Dir::Right, // should be able to pivot downwards
-        coord: (0, 0),
+        coord: Point2::new(0, 0),
         temp_drop: 0,
-        total_cost: to_end((0, 0), &map),
+        total_cost: to_end(Point2::new(0, 0), map),
         tiles_straight: 0,
     };
     let mut queue: BTreeSet<Nav> = BTreeSet::new();
@@ -131,12 +122,12 @@ fn solve(f: &str, t: Turning) -> usize {
     queue.insert(Nav { dir: Dir::Down, ..initial });
     loop {
         let current = queue.pop_first().unwrap(); // we add them faster than we take them
-        if finished(current, &map, t) {
+        if finished(current, map, t) {
             return current.temp_drop;
         }
         let d = current.dir;
         for dir in [d, clockwise(d), counterclockwise(d)] {
-            match create(&map, current, dir, t) {
+            match create(map, current, dir, t) {
                 Some(child) => {
                     let k = (child.coord, child.dir, child.tiles_straight);
                     match visited.get(&k) {
@@ -153,14 +144,19 @@ fn solve(f: &str, t: Turning) -> usize {
     }
 }
 
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let map = r.parse(|| parse_str(input));
+    r.part(1, || least_heat_loss(&map, (0, 3)));
+    r.part(2, || least_heat_loss(&map, (4, 10)));
+}
+
 fn main() {
-    println!("part 1: {:?}", solve("inputs/17b", (0, 3)));
-    println!("part 2: {:?}", solve("inputs/17b", (4, 10)));
+    runner::main("inputs/17b", run);
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use super::*;
     #[test]
     fn part1_test() {
         assert_eq!(102, solve("inputs/17a", (0, 3)));
@@ -1,5 +1,6 @@
+use aoc2023::coord::{Dir, Point2};
+use aoc2023::runner::{self, Runner};
 use itertools::Itertools;
-use std::fs::read_to_string;
 use substring::Substring;
 
 fn horizontal(d: Dir) -> bool {
@@ -9,22 +10,14 @@ fn horizontal(d: Dir) -> bool {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
-enum Dir {
-    Left,
-    Right,
-    Down,
-    Up,
-}
+type Coord = Point2<isize>;
 
-type Coord = (isize, isize);
-
-fn step(d: Dir, (x, y): Coord, n: isize) -> Coord {
+fn step(d: Dir, c: Coord, n: isize) -> Coord {
     match d {
-        Dir::Left => (x - n, y),
-        Dir::Right => (x + n, y),
-        Dir::Down => (x, y + n),
-        Dir::Up => (x, y - n),
+        Dir::Left => c - Point2::new(n, 0),
+        Dir::Right => c + Point2::new(n, 0),
+        Dir::Down => c + Point2::new(0, n),
+        Dir::Up => c - Point2::new(0, n),
     }
 }
 
@@ -61,8 +54,13 @@ fn parse_line2(l: &str) -> Instruction {
     Instruction { dir, length }
 }
 
+#[cfg(test)]
 fn parse(f: &str, parse_line: fn(&str) -> Instruction) -> Vec<Instruction> {
-    read_to_string(f).unwrap().lines().map(parse_line).collect()
+    parse_str(&std::fs::read_to_string(f).unwrap(), parse_line)
+}
+
+fn parse_str(s: &str, parse_line: fn(&str) -> Instruction) -> Vec<Instruction> {
+    s.lines().map(parse_line).collect()
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -182,11 +180,11 @@ fn breakpoints(segments: &Vec<Segment>) -> Vec<isize> {
 fn segmentize(instr: Vec<Instruction>) -> Vec<Segment> {
     instr
         .into_iter()
-        .scan((0, 0), |pos0, i| {
+        .scan(Point2::new(0, 0), |pos0, i| {
             let pos1 = step(i.dir, *pos0, i.length);
-            let start = pos0.0.min(pos1.0);
-            let end = pos0.0.max(pos1.0);
-            let altitude = pos0.1;
+            let start = pos0.x.min(pos1.x);
+            let end = pos0.x.max(pos1.x);
+            let altitude = pos0.y;
             let s = Segment { start, end, altitude };
             *pos0 = pos1;
             if horizontal(i.dir) {
@@ -200,14 +198,20 @@ fn segmentize(instr: Vec<Instruction>) -> Vec<Segment> {
         .collect_vec()
 }
 
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let plan = r.parse(|| parse_str(input, parse_line));
+    let plan2 = r.parse(|| parse_str(input, parse_line2));
+    r.part(1, || solve(plan));
+    r.part(2, || solve(plan2));
+}
+
 fn main() {
-    println!("part 1: {:?}", solve(parse("inputs/18b", parse_line)));
-    println!("part 2: {:?}", solve(parse("inputs/18b", parse_line2)));
+    runner::main("inputs/18b", run);
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use super::*;
     #[test]
     fn parsing_test() {
         assert_eq!(Instruction { dir: Dir::Right, length: 6 }, parse_line("R 6 (#70c710)"));
@@ -1,5 +1,6 @@
 use std::{collections::HashMap, ops::Index, ops::IndexMut};
 
+use aoc2023::runner::{self, Runner};
 use itertools::Itertools;
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -120,8 +121,7 @@ fn parse_workflow_unroll(w: &str) -> Vec<(String, Step)> {
 type Steps = HashMap<String, Step>;
 type Data = (Steps, Vec<Part>);
 
-fn parse(f: &str) -> Data {
-    let content = std::fs::read_to_string(f).unwrap();
+fn parse_str(content: &str) -> Data {
     let (w, p) = content.split("\n\n").collect_tuple().unwrap();
     let steps: Steps = w.lines().flat_map(parse_workflow_unroll).collect();
     let parts = p.lines().map(parse_part).collect();
@@ -273,14 +273,20 @@ fn part2(ws: Steps, init: String) -> isize {
     acc.into_iter().map(possibilities).sum()
 }
 
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let data = r.parse(|| parse_str(input));
+    let steps = data.0.clone();
+    r.part(1, || part1(data, String::from("in")));
+    r.part(2, || part2(steps, String::from("in")));
+}
+
 fn main() {
-    println!("part1: {}", part1(parse("inputs/19b"), String::from("in")));
-    println!("part2: {}", part2(parse("inputs/19b").0, String::from("in")));
+    runner::main("inputs/19b", run);
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use super::*;
     #[test]
     fn parsing_test() {
         assert_eq!(
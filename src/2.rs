@@ -1,32 +1,53 @@
-use std::fs::read_to_string;
+use aoc2023::runner::{self, Runner};
+use itertools::Itertools;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-struct Cubes {
-    red: i32,
-    green: i32,
-    blue: i32,
+// colour -> count, for whatever colours the input comes up with
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Cubes(BTreeMap<String, i32>);
+
+impl Cubes {
+    fn new(counts: &[(&str, i32)]) -> Self {
+        Cubes(counts.iter().map(|(c, n)| (c.to_string(), *n)).collect())
+    }
+
+    // colours that never came up are simply none of them
+    fn get(&self, colour: &str) -> i32 {
+        self.0.get(colour).copied().unwrap_or(0)
+    }
 }
 
-fn parse_subsection(sub: &str) -> (i32, &str) {
-    let (num, s) = sub.trim().split_once(" ").unwrap();
-    (num.parse::<i32>().unwrap(), s)
+// same shape as a draw, e.g. "12 red, 13 green, 14 blue"
+impl FromStr for Cubes {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut acc = Cubes::default();
+        for sub in s.split(',') {
+            let (n, colour) = sub.trim().split_once(' ').ok_or(format!("expected a count and a colour: {sub:?}"))?;
+            let n = n.parse().map_err(|_| format!("{n} is not a count"))?;
+            acc.0.insert(colour.trim().to_string(), n);
+        }
+        Ok(acc)
+    }
 }
 
-// mutation, just for the variety
-fn add_subsection_(acc: &mut Cubes, new: &str) {
-    let (n, s) = parse_subsection(new);
-    match s {
-        "red" => acc.red = n,
-        "green" => acc.green = n,
-        "blue" => acc.blue = n,
-        &_ => panic!("unknown thing: {}", s),
+impl fmt::Display for Cubes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (c, n)) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{n} {c}")?;
+        }
+        Ok(())
     }
 }
 
 fn parse_section(section: &str) -> Cubes {
-    let mut acc = Cubes { red: 0, green: 0, blue: 0 };
-    section.split(",").for_each(|x| add_subsection_(&mut acc, x));
-    acc
+    section.parse().unwrap_or_else(|e| panic!("{e}"))
 }
 
 fn parse_line(line: &str) -> (i32, Vec<Cubes>) {
@@ -36,62 +57,233 @@ fn parse_line(line: &str) -> (i32, Vec<Cubes>) {
     (nl, cubess)
 }
 
-const MAX: Cubes = Cubes {
-    red: 12,
-    green: 13,
-    blue: 14,
-};
+fn max_bag() -> Cubes {
+    Cubes::new(&[("red", 12), ("green", 13), ("blue", 14)])
+}
 
 /// each element of `a` is not less then it's `b` counterpart
-fn fits(a: Cubes, b: Cubes) -> bool {
-    a.red >= b.red && a.green >= b.green && a.blue >= b.blue
+fn fits(a: &Cubes, b: &Cubes) -> bool {
+    b.0.iter().all(|(c, &n)| a.get(c) >= n)
 }
 
-fn game_possible(xs: Vec<Cubes>) -> bool {
-    xs.iter().copied().all(|x| fits(MAX, x))
+fn game_possible(bag: &Cubes, xs: &[Cubes]) -> bool {
+    xs.iter().all(|x| fits(bag, x))
 }
 
-fn solve(f: &str) -> i32 {
-    read_to_string(f)
-        .unwrap()
-        .lines()
-        .map(parse_line)
-        .filter_map(|(n, xs)| if game_possible(xs) { Some(n) } else { None })
+type Game = (i32, Vec<Cubes>);
+
+// every colour seen in any of the games
+fn palette(games: &[Game]) -> BTreeSet<&str> {
+    games.iter().flat_map(|(_, xs)| xs).flat_map(|x| x.0.keys()).map(String::as_str).collect()
+}
+
+fn parse_str(s: &str) -> Vec<Game> {
+    s.lines().map(parse_line).collect()
+}
+
+fn possible_ids(bag: &Cubes, games: &[Game]) -> i32 {
+    games
+        .iter()
+        .filter_map(|(n, xs)| if game_possible(bag, xs) { Some(n) } else { None })
         .sum()
 }
 
-fn maximum(a: Cubes, b: Cubes) -> Cubes {
-    println!("blu {}", a.blue);
-    let max = |x, y| if y > x { y } else { x };
-    Cubes {
-        red: max(a.red, b.red),
-        green: max(a.green, b.green),
-        blue: max(a.blue, b.blue),
+#[cfg(test)]
+fn solve(f: &str) -> i32 {
+    possible_ids(&max_bag(), &parse_str(&std::fs::read_to_string(f).unwrap()))
+}
+
+fn maximum(a: Cubes, b: &Cubes) -> Cubes {
+    let mut acc = a;
+    for (c, &n) in &b.0 {
+        let m = acc.0.entry(c.clone()).or_insert(n);
+        *m = (*m).max(n);
     }
+    acc
 }
 
-fn power(c: Cubes) -> i32 {
-    c.red * c.green * c.blue
+// a colour of the palette missing from `c` makes it zero, same as with fixed colours
+fn power(c: &Cubes, palette: &BTreeSet<&str>) -> i32 {
+    palette.iter().map(|p| c.get(p)).product()
 }
 
+// the fewest cubes of each colour the game could have been played with
+fn minimal_bag(xs: &[Cubes]) -> Cubes {
+    xs.iter().fold(Cubes::default(), maximum)
+}
+
+fn total_power(games: &[Game]) -> i32 {
+    let palette = palette(games);
+    games.iter().map(|(_, xs)| power(&minimal_bag(xs), &palette)).sum()
+}
+
+// one colour of one draw that the bag can't cover, draws count from 1
+#[derive(Debug, PartialEq)]
+struct Excess {
+    draw: usize,
+    colour: String,
+    count: i32,
+    over: i32,
+}
+
+// a game is possible when nothing is in excess
+#[derive(Debug, PartialEq)]
+struct GameReport {
+    id: i32,
+    excess: Vec<Excess>,
+    minimal: Cubes,
+}
+
+impl fmt::Display for GameReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for e in &self.excess {
+            write!(f, "draw {} has {} {} ({} over); ", e.draw, e.count, e.colour, e.over)?;
+        }
+        write!(f, "minimal bag {}", self.minimal)
+    }
+}
+
+// why each impossible game is impossible with `bag`, and the minimal bag of every game
+fn game_reports(bag: &Cubes, games: &[Game]) -> Vec<GameReport> {
+    games
+        .iter()
+        .map(|(id, xs)| {
+            let excess: Vec<Excess> = xs
+                .iter()
+                .enumerate()
+                .flat_map(|(i, x)| {
+                    x.0.iter().filter(|(c, &n)| n > bag.get(c)).map(move |(c, &n)| Excess {
+                        draw: i + 1,
+                        colour: c.clone(),
+                        count: n,
+                        over: n - bag.get(c),
+                    })
+                })
+                .collect();
+            GameReport {
+                id: *id,
+                excess,
+                minimal: minimal_bag(xs),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
 fn solve2(f: &str) -> i32 {
-    read_to_string(f)
-        .unwrap()
-        .lines()
-        .map(parse_line)
-        .map(|(_, xs)| xs.iter().copied().fold(Cubes { red: 0, green: 0, blue: 0 }, |acc, y| maximum(acc, y)))
-        .map(power)
-        .sum()
+    total_power(&parse_str(&std::fs::read_to_string(f).unwrap()))
+}
+
+// what makes one bag smaller than another
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Size {
+    Count,
+    // ties go to the bag with fewer cubes
+    Power,
+}
+
+impl FromStr for Size {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "count" => Ok(Size::Count),
+            "power" => Ok(Size::Power),
+            _ => Err(format!("no such size: {s}")),
+        }
+    }
+}
+
+fn score(bag: &Cubes, size: Size) -> (i64, i64) {
+    let count = bag.0.values().map(|&n| n as i64).sum();
+    match size {
+        Size::Count => (count, 0),
+        Size::Power => (bag.0.values().map(|&n| n as i64).product(), count),
+    }
+}
+
+// the maximum of their minimal bags, None when one of the games isn't there
+fn bag_for(games: &[Game], ids: &[i32]) -> Option<Cubes> {
+    ids.iter().try_fold(Cubes::default(), |acc, id| {
+        let (_, xs) = games.iter().find(|(n, _)| n == id)?;
+        Some(maximum(acc, &minimal_bag(xs)))
+    })
+}
+
+// smallest bag with at least `k` possible games, None when there aren't that many.
+// every count in the best bag is one some game needs, so try those colour by colour
+fn smallest_bag(games: &[Game], k: usize, size: Size) -> Option<Cubes> {
+    if k > games.len() {
+        return None;
+    }
+    if k == 0 {
+        return Some(Cubes::default());
+    }
+    let colours = palette(games).into_iter().collect_vec();
+    let bags = games.iter().map(|(_, xs)| minimal_bag(xs)).collect_vec();
+    let mut best = None;
+    search(&bags.iter().collect_vec(), &colours, k, size, &mut Cubes::default(), &mut best);
+    best.map(|(_, bag)| bag)
+}
+
+// `bag` has the colours picked so far and `left` the games that still fit it
+fn search(left: &[&Cubes], colours: &[&str], k: usize, size: Size, bag: &mut Cubes, best: &mut Option<((i64, i64), Cubes)>) {
+    let Some((c, rest)) = colours.split_first() else {
+        let s = score(bag, size);
+        if best.as_ref().is_none_or(|(b, _)| s < *b) {
+            *best = Some((s, bag.clone()));
+        }
+        return;
+    };
+    for v in left.iter().map(|b| b.get(c)).sorted().dedup() {
+        let fit = left.iter().copied().filter(|b| b.get(c) <= v).collect_vec();
+        if fit.len() < k {
+            continue;
+        }
+        bag.0.insert(c.to_string(), v);
+        search(&fit, rest, k, size, bag, best);
+        // more of the last colour, or more than anyone left needs, can only be worse
+        if rest.is_empty() || fit.len() == left.len() {
+            break;
+        }
+    }
+}
+
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let games = r.parse(|| parse_str(input));
+    let bag = r.param("bag", max_bag());
+    r.part(1, || possible_ids(&bag, &games));
+    r.part(2, || total_power(&games));
+    if r.param("report", false) {
+        let report = r.step("report", || game_reports(&bag, &games));
+        r.note("impossible games", report.iter().filter(|g| !g.excess.is_empty()).count());
+        for v in report {
+            r.note(&format!("game {}", v.id), v);
+        }
+    }
+    let k: usize = r.param("at-least", 0);
+    if k > 0 {
+        let size = r.param("size", Size::Count);
+        let bag = r.step("infer", || smallest_bag(&games, k, size));
+        r.note(&format!("smallest bag for {k} games"), bag.map_or("none".to_string(), |b| b.to_string()));
+    }
+    let ids: String = r.param("ids", String::new());
+    if !ids.is_empty() {
+        let ids = ids.split(',').map(|s| s.trim().parse().unwrap()).collect_vec();
+        let bag = r.step("infer", || bag_for(&games, &ids));
+        r.note(
+            &format!("smallest bag for games {}", ids.iter().join(",")),
+            bag.map_or("none".to_string(), |b| b.to_string()),
+        );
+    }
 }
 
 fn main() {
-    println!("part 1: {}", solve("inputs/2b"));
-    println!("part 2: {}", solve2("inputs/2b"));
+    runner::main("inputs/2b", run);
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use super::*;
 
     #[test]
     fn parse_test() {
@@ -101,26 +293,102 @@ mod tests {
             (
                 1,
                 vec![
-                    Cubes { red: 4, green: 0, blue: 3 },
-                    Cubes { red: 1, green: 2, blue: 6 },
-                    Cubes { red: 0, green: 2, blue: 0 }
+                    Cubes::new(&[("red", 4), ("blue", 3)]),
+                    Cubes::new(&[("red", 1), ("green", 2), ("blue", 6)]),
+                    Cubes::new(&[("green", 2)])
                 ]
             ),
             parse_line(line)
         );
-        assert_eq!(Cubes { red: 1, green: 2, blue: 6 }, parse_section(ss));
-        assert_eq!((6, "blue"), parse_subsection(" 6 blue"));
+        assert_eq!(Cubes::new(&[("red", 1), ("green", 2), ("blue", 6)]), parse_section(ss));
+        assert_eq!(Cubes::new(&[("blue", 6)]), parse_section(" 6 blue"));
     }
     #[test]
     fn comp_test() {
-        let a = Cubes { red: 0, green: 2, blue: 0 };
-        let b = Cubes { red: 0, green: 3, blue: 0 };
-        let c = Cubes { red: 0, green: 0, blue: 2 };
-        assert!(!fits(a, b));
-        assert!(fits(b, a));
-        assert!(fits(a, a));
-        assert!(!fits(a, c));
-        assert!(!fits(c, a));
+        let a = Cubes::new(&[("red", 0), ("green", 2), ("blue", 0)]);
+        let b = Cubes::new(&[("red", 0), ("green", 3), ("blue", 0)]);
+        let c = Cubes::new(&[("red", 0), ("green", 0), ("blue", 2)]);
+        assert!(!fits(&a, &b));
+        assert!(fits(&b, &a));
+        assert!(fits(&a, &a));
+        assert!(!fits(&a, &c));
+        assert!(!fits(&c, &a));
+        assert!(fits(&Cubes::new(&[("green", 2)]), &a));
+    }
+    #[test]
+    fn extra_colour_test() {
+        let games = parse_str("Game 1: 3 blue, 4 yellow; 1 red, 2 green\nGame 2: 1 red, 1 green, 1 blue\nGame 3: 2 yellow, 2 red, 2 green, 5 blue");
+        assert_eq!(vec!["blue", "green", "red", "yellow"], palette(&games).into_iter().collect::<Vec<_>>());
+        assert_eq!(
+            Cubes::new(&[("red", 1), ("green", 2), ("blue", 3), ("yellow", 4)]),
+            games[0].1.iter().fold(Cubes::default(), maximum)
+        );
+        // the bag has no yellow cubes
+        assert_eq!(2, possible_ids(&max_bag(), &games));
+        assert_eq!(6, possible_ids(&"2 red, 2 green, 5 blue, 4 yellow".parse().unwrap(), &games));
+        // game 2 never shows yellow, so its power is zero
+        assert_eq!(24 + 40, total_power(&games));
+    }
+    #[test]
+    fn report_test() {
+        let games = parse_str(&std::fs::read_to_string("inputs/2a").unwrap());
+        let report = game_reports(&max_bag(), &games);
+        assert_eq!(vec![1, 2, 3, 4, 5], report.iter().map(|g| g.id).collect::<Vec<_>>());
+        let impossible = report.iter().filter(|g| !g.excess.is_empty()).collect::<Vec<_>>();
+        assert_eq!(vec![3, 4], impossible.iter().map(|g| g.id).collect::<Vec<_>>());
+        let first = "draw 1 has 20 red (8 over); minimal bag 6 blue, 13 green, 20 red";
+        assert_eq!(first, impossible[0].to_string());
+        let excess = impossible[1].excess.iter().map(|e| (e.draw, e.colour.as_str(), e.over));
+        assert_eq!(vec![(3, "blue", 1), (3, "red", 2)], excess.collect::<Vec<_>>());
+        assert_eq!("minimal bag 6 blue, 2 green, 4 red", report[0].to_string());
+        let roomy = game_reports(&"20 red, 13 green, 15 blue".parse().unwrap(), &games);
+        assert!(roomy.iter().all(|g| g.excess.is_empty()));
+        assert_eq!(Err("x is not a count".to_string()), "x red".parse::<Cubes>());
+
+        let mut r = Runner::new();
+        r.params.insert("bag".to_string(), "20 red, 13 green, 15 blue".to_string());
+        r.params.insert("report".to_string(), "true".to_string());
+        run(&std::fs::read_to_string("inputs/2a").unwrap(), &mut r);
+        assert_eq!(Some("15"), r.answer(1));
+        assert_eq!(("impossible games".to_string(), "0".to_string()), r.notes[0]);
+        assert_eq!(("game 5".to_string(), "minimal bag 2 blue, 3 green, 6 red".to_string()), r.notes[5]);
+    }
+    #[test]
+    fn infer_test() {
+        let games = parse_str(&std::fs::read_to_string("inputs/2a").unwrap());
+        assert_eq!(Some(Cubes::new(&[("red", 4), ("green", 3), ("blue", 6)])), bag_for(&games, &[1, 2]));
+        assert_eq!(None, bag_for(&games, &[1, 9]));
+        assert_eq!(
+            Some(Cubes::new(&[("red", 1), ("green", 3), ("blue", 4)])),
+            smallest_bag(&games, 1, Size::Count)
+        );
+        assert_eq!(
+            Some(Cubes::new(&[("red", 6), ("green", 3), ("blue", 6)])),
+            smallest_bag(&games, 3, Size::Power)
+        );
+        assert_eq!(bag_for(&games, &[1, 2, 3, 4, 5]), smallest_bag(&games, 5, Size::Count));
+        assert_eq!(None, smallest_bag(&games, 6, Size::Count));
+
+        // lopsided bags have fewer cubes, even ones more power
+        let games = parse_str("Game 1: 8 red, 1 green\nGame 2: 7 red, 1 green\nGame 3: 3 red, 3 green\nGame 4: 2 red, 3 green");
+        assert_eq!(Some(Cubes::new(&[("red", 3), ("green", 3)])), smallest_bag(&games, 2, Size::Count));
+        assert_eq!(Some(Cubes::new(&[("red", 8), ("green", 1)])), smallest_bag(&games, 2, Size::Power));
+        assert_eq!(Some(Cubes::default()), smallest_bag(&games, 0, Size::Power));
+    }
+    #[test]
+    fn infer_param_test() {
+        let mut r = Runner::new();
+        r.params.insert("at-least".to_string(), "2".to_string());
+        r.params.insert("size".to_string(), "power".to_string());
+        r.params.insert("ids".to_string(), "3, 5".to_string());
+        run(&std::fs::read_to_string("inputs/2a").unwrap(), &mut r);
+        assert_eq!(
+            vec![
+                ("smallest bag for 2 games".to_string(), "4 blue, 3 green, 6 red".to_string()),
+                ("smallest bag for games 3,5".to_string(), "6 blue, 13 green, 20 red".to_string())
+            ],
+            r.notes
+        );
     }
     #[test]
     fn part1() {
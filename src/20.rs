@@ -1,8 +1,7 @@
-use std::{
-    collections::{HashMap, HashSet},
-    fs::read_to_string,
-};
+use std::collections::{HashMap, HashSet};
 
+use aoc2023::cycle::{self, Cycle};
+use aoc2023::runner::{self, Runner};
 use itertools::Itertools;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -162,8 +161,7 @@ fn button(n: Network, observed: &mut HashMap<(String, String), isize>) -> (Netwo
     (n, counter)
 }
 
-fn parse(f: &str) -> Network {
-    let content = read_to_string(f).unwrap();
+fn parse_str(content: &str) -> Network {
     let modules = content.lines().map(pre_parse_module).collect_vec();
     let inputs = collect_inputs(&modules);
     modules
@@ -231,9 +229,91 @@ struct Machine {
     targets: Vec<String>,
 }
 
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let network = r.parse(|| parse_str(input));
+    r.part(1, || part1(network.clone()));
+    r.part(2, || part2(network));
+}
+
 fn main() {
-    println!("part1: {:?}", part1(parse("inputs/20b")));
-    println!("part2: {:?}", part2(parse("inputs/20b")));
+    runner::main("inputs/20b", run);
+}
+
+// sorted, so it can be hashed
+type State = Vec<(String, Machine)>;
+
+// everything that can send pulses to `name`, `name` included
+fn collect_upstream(name: &str, network: &Network) -> HashSet<String> {
+    let mut acc: HashSet<String> = HashSet::from([name.to_owned()]);
+    let mut todo = vec![name.to_owned()];
+    while let Some(current) = todo.pop() {
+        for (n, m) in network {
+            if m.targets.contains(&current) && acc.insert(n.to_owned()) {
+                todo.push(n.to_owned());
+            }
+        }
+    }
+    acc
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// x = a mod m and x = b mod n as one residue mod lcm(m, n), if they agree at all
+fn crt(a: usize, m: usize, b: usize, n: usize) -> Option<(usize, usize)> {
+    let g = gcd(m, n);
+    let (a, b, m, n, g) = (a as i128, b as i128, m as i128, n as i128, g as i128);
+    if (b - a) % g != 0 {
+        return None;
+    }
+    // extended euclid for the inverse of m / g mod n / g
+    let (mut r0, mut r1, mut x0, mut x1) = (m / g % (n / g), n / g, 1, 0);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1, x0, x1) = (r1, r0 - q * r1, x1, x0 - q * x1);
+    }
+    let k = ((b - a) / g * x0).rem_euclid(n / g);
+    let lcm = m / g * n;
+    Some(((a + m * k).rem_euclid(lcm) as usize, lcm as usize))
+}
+
+// the presses a counter fires on, counted from 1 and up to the end of its first period
+struct Fires {
+    cycle: Cycle,
+    presses: Vec<usize>,
+}
+
+impl Fires {
+    // press `n` is the one leaving state `n - 1`
+    fn on(&self, n: usize) -> bool {
+        self.presses.contains(&(self.cycle.reduce(n - 1) + 1))
+    }
+}
+
+// the first press they all fire on. until every counter is going round it's just a matter of
+// looking, past that each fires on a few residues of its period, and those go through crt
+fn first_together(fires: &[Fires]) -> Option<usize> {
+    let settled = fires.iter().map(|f| f.cycle.start).max().unwrap_or(0);
+    if let Some(n) = (1..=settled).find(|&n| fires.iter().all(|f| f.on(n))) {
+        return Some(n);
+    }
+    let residues = fires.iter().fold(vec![(0, 1)], |acc, f| {
+        let (start, period) = (f.cycle.start, f.cycle.period);
+        let mine = f.presses.iter().filter(|&&n| n > start).map(|n| n % period).collect_vec();
+        acc.iter()
+            .flat_map(|&(a, m)| mine.iter().filter_map(move |&b| crt(a, m, b, period)))
+            .collect()
+    });
+    // the first one past `settled`
+    residues
+        .into_iter()
+        .map(|(r, m)| if r > settled { r } else { r + ((settled - r) / m + 1) * m })
+        .min()
 }
 
 fn part2(network: Network) -> usize {
@@ -243,23 +323,32 @@ fn part2(network: Network) -> usize {
         Module::Conj(x) => x.into_iter().map(|(x, _)| x).collect_vec(),
         _ => panic!(),
     };
-    let obs: HashMap<(String, String), isize> = last_ones.to_owned().iter().map(|x| ((x.to_owned(), merger.to_owned()), 0)).collect();
-    let mut cycles: HashMap<String, usize> = HashMap::new();
-    let mut s = network.clone();
-    let mut i = 0;
-    loop {
-        i = i + 1;
-        let mut o = obs.clone();
-        s = button(s.to_owned(), &mut o).0;
-        for ((key, _), val) in o {
-            if val > 0 {
-                cycles.insert(key, i);
-                if cycles.len() == last_ones.len() {
-                    return cycles.iter().fold(1, |acc, (_, x)| acc * x);
-                }
-            }
-        }
-    }
+    // each of them sits on its own counter, which goes round and fires high on the way,
+    // rx gets its low pulse on the first press they all fire on
+    let fires = last_ones
+        .iter()
+        .map(|last| {
+            let upstream = collect_upstream(last, &network);
+            let sub: State = network
+                .iter()
+                .filter(|(n, _)| upstream.contains(*n))
+                .map(|(n, m)| (n.to_owned(), m.to_owned()))
+                .sorted()
+                .collect();
+            let edge = (last.to_owned(), merger.to_owned());
+            let mut fired = vec![];
+            let press = |s: &State| {
+                let mut observed = HashMap::from([(edge.clone(), 0)]);
+                let (n, _) = button(s.iter().cloned().collect(), &mut observed);
+                fired.push(observed[&edge] > 0);
+                n.into_iter().sorted().collect()
+            };
+            let (cycle, _) = cycle::record(sub, press);
+            let presses = fired.iter().positions(|&f| f).map(|i| i + 1).collect();
+            Fires { cycle, presses }
+        })
+        .collect_vec();
+    first_together(&fires).expect("the counters never fire together")
 }
 
 fn find_merger(impacts: &HashMap<String, HashSet<String>>) -> String {
@@ -278,3 +367,24 @@ fn calc_total_impact(network: HashMap<String, Machine>) -> HashMap<String, HashS
     }
     impact
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_together_test() {
+        let fires = |start, period, presses| Fires {
+            cycle: Cycle { start, period },
+            presses,
+        };
+        assert_eq!(Some((9, 12)), crt(1, 4, 3, 6));
+        assert_eq!(None, crt(2, 4, 3, 6));
+        assert_eq!(Some(12), first_together(&[fires(0, 4, vec![4]), fires(0, 6, vec![6])]));
+        // going round only after 3 presses, firing on 5, 9, 13, ...
+        assert_eq!(Some(9), first_together(&[fires(3, 4, vec![5]), fires(0, 3, vec![3])]));
+        // firing on 2 the once
+        assert_eq!(Some(2), first_together(&[fires(3, 4, vec![2, 5]), fires(0, 2, vec![2])]));
+        assert_eq!(None, first_together(&[fires(0, 2, vec![1]), fires(0, 2, vec![2])]));
+    }
+}
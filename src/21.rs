@@ -1,6 +1,7 @@
+use aoc2023::coord::{Dir, Point2, DIRECTIONS};
+use aoc2023::runner::{self, Runner};
 use itertools::Itertools;
 use std::collections::{HashMap, VecDeque};
-use std::fs::read_to_string;
 use std::ops::{Index, IndexMut};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -9,23 +10,10 @@ enum Tile {
     Blocked,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-enum Dir {
-    Left,
-    Right,
-    Down,
-    Up,
-}
+type Coord = Point2<i32>;
 
-type Coord = (i32, i32);
-
-fn step(d: Dir, (x, y): Coord) -> Coord {
-    match d {
-        Dir::Left => (x - 1, y),
-        Dir::Right => (x + 1, y),
-        Dir::Down => (x, y + 1),
-        Dir::Up => (x, y - 1),
-    }
+fn step(d: Dir, c: Coord) -> Coord {
+    c.step(d).unwrap()
 }
 
 //type Map = Vec<Vec<Tile>>;
@@ -42,14 +30,14 @@ fn modulo(a: i32, b: i32) -> usize {
 
 impl Index<Coord> for Map {
     type Output = Tile;
-    fn index(&self, (x, y): Coord) -> &Tile {
-        &self.tiles[modulo(y, self.size.1)][modulo(x, self.size.0)]
+    fn index(&self, Point2 { x, y }: Coord) -> &Tile {
+        &self.tiles[modulo(y, self.size.y)][modulo(x, self.size.x)]
     }
 }
 
 impl IndexMut<Coord> for Map {
-    fn index_mut(&mut self, (x, y): Coord) -> &mut Tile {
-        &mut self.tiles[modulo(y, self.size.1)][modulo(x, self.size.0)]
+    fn index_mut(&mut self, Point2 { x, y }: Coord) -> &mut Tile {
+        &mut self.tiles[modulo(y, self.size.y)][modulo(x, self.size.x)]
     }
 }
 
@@ -61,10 +49,9 @@ fn parse_tile(t: char) -> Tile {
     }
 }
 
-fn parse(f: &str) -> Map {
-    let mut start = (0, 0);
-    let tiles = read_to_string(f)
-        .unwrap()
+fn parse_str(s: &str) -> Map {
+    let mut start = Point2::new(0, 0);
+    let tiles = s
         .lines()
         .enumerate()
         .map(|(y, l)| {
@@ -72,19 +59,17 @@ fn parse(f: &str) -> Map {
                 .enumerate()
                 .map(|(x, c)| {
                     if c == 'S' {
-                        start = (x as i32, y as i32)
+                        start = Point2::new(x as i32, y as i32)
                     };
                     parse_tile(c)
                 })
                 .collect_vec()
         })
         .collect_vec();
-    let size = (tiles[0].len() as i32, tiles.len() as i32);
+    let size = Point2::new(tiles[0].len() as i32, tiles.len() as i32);
     Map { tiles, start, size }
 }
 
-static DIRECTIONS: &[Dir] = &[Dir::Left, Dir::Right, Dir::Up, Dir::Down];
-
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 enum Mod2 {
     Odd,
@@ -155,26 +140,17 @@ fn part1(m: &Map, age: usize) -> usize {
     res
 }
 
-fn diff(m:&Map, age: usize ) -> usize {
-    let exc = (age+1) * (age+1);
-    exc - part1(m, age)
-}
-fn part2(m: &Map, age: usize) -> usize {
-    let diameter = 131;
-    let radius = 65;
-    //for a in 60..70 {
-    for a in 60..70 {
-        println!("{a} -> {}", diff(&m, a));
-    }
-    3
+// part 2 is not there yet
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let m = r.parse(|| parse_str(input));
+    let steps = r.param("steps", 64);
+    r.part(1, || part1(&m, steps));
 }
 
-
 fn main() {
     //println!("part 1: {:?}", part1(parse("inputs/21a"), 6));
-    //println!("part 1: {:?}", part1(parse("inputs/21b"), 64));
-    println!("part 2: {:?}", part2(&parse("inputs/21b"), 64*3+3));
     //println!("part 2: {:?}", part2(parse("inputs/10b"), Dir::Up));
+    runner::main("inputs/21b", run);
 }
 
 #[cfg(test)]
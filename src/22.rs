@@ -1,13 +1,10 @@
 use std::collections::{HashMap, HashSet, VecDeque};
 
+use aoc2023::coord::Point3;
+use aoc2023::runner::{self, Runner};
 use itertools::Itertools;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
-struct Coord {
-    x: isize,
-    y: isize,
-    z: isize,
-}
+type Coord = Point3<isize>;
 
 impl std::ops::Index<Axis> for Coord {
     type Output = isize;
@@ -123,8 +120,8 @@ fn parse_brick((id, l): (usize, &str)) -> Brick {
     normalize(Brick { start, end, id })
 }
 
-fn parse(f: &str) -> Vec<Brick> {
-    std::fs::read_to_string(f).unwrap().lines().enumerate().map(parse_brick).collect_vec()
+fn parse_str(s: &str) -> Vec<Brick> {
+    s.lines().enumerate().map(parse_brick).collect_vec()
 }
 
 fn collisions(bricks: &Vec<Brick>, down: Brick) -> Vec<usize> {
@@ -191,10 +188,15 @@ fn part2(bricks: Vec<Brick>) -> usize {
     bricks.iter().map(|x| desintegration_impact(x.id, &deps)).sum()
 }
 
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let bricks = r.parse(|| parse_str(input));
+    let data = r.step("fall", || fall(bricks));
+    r.part(1, || part1(data.clone()));
+    r.part(2, || part2(data));
+}
+
 fn main() {
-    let data = fall(parse("inputs/22a"));
-    println!("part 1: {:?}", part1(data.clone()));
-    println!("part 2: {:?}", part2(data.clone()));
+    runner::main("inputs/22a", run);
 }
 
 #[cfg(test)]
@@ -218,5 +220,5 @@ mod tests {
         assert_eq!(true, collides(parse_brick((0, "1,2,1~1,3,1")), parse_brick((1, "1,0,1~1,2,1"))));
         assert_eq!(false, collides(parse_brick((0, "1,3,1~1,4,1")), parse_brick((1, "1,0,1~1,2,1"))));
     }
-    use crate::*;
+    use super::*;
 }
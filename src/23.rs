@@ -1,6 +1,7 @@
+use aoc2023::coord::{Dir, Point2};
+use aoc2023::runner::{self, Runner};
 use itertools::Itertools;
 use std::collections::{vec_deque, HashMap, HashSet, VecDeque};
-use std::fs::read_to_string;
 use std::ops::Index;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -23,27 +24,13 @@ fn opposite(x: Dir) -> Dir {
     clockwise(clockwise(x))
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
-enum Dir {
-    Left,
-    Right,
-    Down,
-    Up,
-}
-
-type Addr = u32; // could be u16 ( ***i think*** )
+// u16 is plenty for 141x141
+type Addr = u16;
 
-type Coord = (Addr, Addr);
+type Coord = Point2<Addr>;
 
-fn step(d: Dir, (x, y): Coord, m: &Map) -> Option<Coord> {
-    let (mx, my) = m.size;
-    match d {
-        Dir::Left if x > 0 => Some((x - 1, y)),
-        Dir::Right if x < (mx - 1) => Some((x + 1, y)),
-        Dir::Down if y < (my - 1) => Some((x, y + 1)),
-        Dir::Up if y > 0 => Some((x, y - 1)),
-        _ => None,
-    }
+fn step(d: Dir, c: Coord, m: &Map) -> Option<Coord> {
+    c.step_within(d, m.size)
 }
 
 fn advance(s: Scanner, m: &Map) -> Option<Scanner> {
@@ -62,7 +49,7 @@ struct Map {
 
 impl Index<Coord> for Map {
     type Output = Tile;
-    fn index(&self, (x, y): Coord) -> &Tile {
+    fn index(&self, Point2 { x, y }: Coord) -> &Tile {
         &self.tiles[y as usize][x as usize]
     }
 }
@@ -86,11 +73,11 @@ fn parse_tile(t: char) -> Tile {
     }
 }
 
-fn parse(f: &str, pt: fn(char) -> Tile) -> Map {
-    let tiles = read_to_string(f).unwrap().lines().map(|l| l.chars().map(pt).collect_vec()).collect_vec();
-    let size = (tiles[0].len() as Addr, tiles.len() as Addr);
-    let start = (1, 0);
-    let end = ((size.0 - 2) as Addr, (size.1 - 1) as Addr);
+fn parse_str(s: &str, pt: fn(char) -> Tile) -> Map {
+    let tiles = s.lines().map(|l| l.chars().map(pt).collect_vec()).collect_vec();
+    let size = Point2::new(tiles[0].len(), tiles.len()).cast::<Addr>().expect("map too big for Addr");
+    let start = Point2::new(1, 0);
+    let end = Point2::new(size.x - 2, size.y - 1);
     Map { tiles, start, end, size }
 }
 
@@ -110,21 +97,21 @@ struct Scanner {
 }
 
 fn all_around(map: &Map) -> impl Iterator<Item = Scanner> + '_ {
-    let down = (0..map.size.0).map(|x| Scanner {
+    let down = (0..map.size.x).map(|x| Scanner {
         dir: Dir::Down,
-        coord: (x, 0),
+        coord: Point2::new(x, 0),
     });
-    let up = (0..map.size.0).map(|x| Scanner {
+    let up = (0..map.size.x).map(|x| Scanner {
         dir: Dir::Up,
-        coord: (x, map.size.1 - 1),
+        coord: Point2::new(x, map.size.y - 1),
     });
-    let right = (0..map.size.1).map(|y| Scanner {
+    let right = (0..map.size.y).map(|y| Scanner {
         dir: Dir::Right,
-        coord: (0, y),
+        coord: Point2::new(0, y),
     });
-    let left = (0..map.size.1).map(|y| Scanner {
+    let left = (0..map.size.y).map(|y| Scanner {
         dir: Dir::Left,
-        coord: (map.size.0 - 1, y),
+        coord: Point2::new(map.size.x - 1, y),
     });
     left.chain(right).chain(up).chain(down)
 }
@@ -333,10 +320,13 @@ fn part1(m: Map, n: Nodes) -> usize {
     rec_part(&n, target, vec![first.start], first, first.length)
 }
 // 6734 too high
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let m = r.parse(|| parse_str(input, parse_tile_noslip));
+    let nodes = r.step("nodes", || get_nodes(m.clone()));
+    let n = r.step("merge", || merge(nodes));
+    r.part(1, || part1(m, n));
+}
+
 fn main() {
-    let m = parse("inputs/23b", parse_tile_noslip);
-    let nodes = get_nodes(m.clone());
-    let n = merge(nodes.clone());
-    //println!("a: {:?}, b: {:?}", nodes.starts.len(), n.starts.len());
-    println!("part 1: {:?}", part1(m.clone(), n.clone()));
+    runner::main("inputs/23b", run);
 }
@@ -1,5 +1,6 @@
 use std::collections::{HashMap, HashSet, VecDeque};
 
+use aoc2023::runner::{self, Runner};
 use itertools::{Itertools, Position};
 
 #[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
@@ -97,7 +98,11 @@ struct Hail {
 }
 
 fn parse(f: &str) -> Vec<Hail> {
-    std::fs::read_to_string(f).unwrap().lines().map(parse_hail).collect()
+    parse_str(&std::fs::read_to_string(f).unwrap())
+}
+
+fn parse_str(s: &str) -> Vec<Hail> {
+    s.lines().map(parse_hail).collect()
 }
 
 fn part1(hails: &Vec<Hail>, mm: MM) -> usize {
@@ -108,9 +113,15 @@ fn part1(hails: &Vec<Hail>, mm: MM) -> usize {
         .count()
 }
 
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let hails = r.parse(|| parse_str(input));
+    let bounds = (r.param("min", 200000000000000.), r.param("max", 400000000000000.));
+    r.part(1, || part1(&hails, bounds));
+}
+
 fn main() {
-    println!("{:?}", part1(&parse("inputs/24a"), (7., 27.)));
-    println!("{:?}", part1(&parse("inputs/24b"), (200000000000000., 400000000000000.)));
+    println!("example: {:?}", part1(&parse("inputs/24a"), (7., 27.)));
+    runner::main("inputs/24b", run);
 }
 
 fn close_enough(a: f64, b: f64) -> bool {
@@ -119,7 +130,7 @@ fn close_enough(a: f64, b: f64) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use super::*;
     #[test]
     fn linification_test() {
         let mkxy = |x, y| Vector { x, y, z: 0. };
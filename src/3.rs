@@ -1,130 +1,493 @@
-use std::{convert::TryInto, fs::read_to_string};
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufRead};
+use std::ops::Range;
+use std::str::FromStr;
 
+use aoc2023::runner::{self, Runner};
 use itertools::Itertools;
 
-fn is_symbol(t: char) -> bool {
-    t != '.' && !t.is_ascii_digit()
-}
-
-fn solve(f: &str, part2: bool) -> i32 {
-    let neighs = vec![
-        (1, -1),
-        (1, 0),
-        (1, 1),
-        // --
-        (0, -1),
-        //
-        (0, 1),
-        // --
-        (-1, -1),
-        (-1, 0),
-        (-1, 1),
-    ];
-    let lines: Vec<Vec<char>> = read_to_string(f).unwrap().lines().map(|x| x.chars().collect_vec()).collect_vec();
-    let sizey: i32 = lines.len().try_into().unwrap();
-    let sizex: i32 = lines.first().unwrap().len().try_into().unwrap();
-    let mut acc = 0;
-    let mut current: i32 = 0;
-    let mut current_counts = false;
-    let mut reading = false;
-    let get = |x: i32, y: i32| -> char {
-        if x < 0 || y < 0 {
-            return '.';
+// which cells around a digit a symbol has to be on
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Neighbourhood {
+    Four,
+    Eight,
+    // chebyshev distance, `Radius(1)` is the same as `Eight`
+    Radius(usize),
+}
+
+impl Neighbourhood {
+    // how many rows away a neighbour can be
+    fn reach(self) -> usize {
+        match self {
+            Neighbourhood::Four | Neighbourhood::Eight => 1,
+            Neighbourhood::Radius(r) => r,
         }
-        match lines.get(usize::try_from(y).unwrap()) {
-            None => '.',
-            Some(line) => match line.get(usize::try_from(x).unwrap()) {
-                None => '.',
-                Some(&c) => c,
+    }
+
+    fn offsets(self) -> Vec<(i32, i32)> {
+        let r = self.reach() as i32;
+        (-r..=r)
+            .cartesian_product(-r..=r)
+            .filter(|&(yo, xo)| (yo, xo) != (0, 0) && (self != Neighbourhood::Four || yo == 0 || xo == 0))
+            .collect()
+    }
+}
+
+// "4", "8", or "r2" for a radius
+impl FromStr for Neighbourhood {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "4" => Ok(Neighbourhood::Four),
+            "8" => Ok(Neighbourhood::Eight),
+            _ => match s.strip_prefix('r').map(str::parse) {
+                Some(Ok(r)) => Ok(Neighbourhood::Radius(r)),
+                _ => Err(format!("no such neighbourhood: {s}")),
             },
         }
-    };
+    }
+}
 
-    let add_digit = |current: i32, dgt: char| -> i32 {
-        let n = dgt.to_digit(10).unwrap();
-        let a = current * 10 + n as i32;
-        a
-    };
+// the conventions a schematic is drawn with
+#[derive(Clone, Debug, PartialEq)]
+struct Style {
+    neighbourhood: Neighbourhood,
+    blanks: String,
+    // None for anything that is neither a digit nor blank, other chars are ignored
+    symbols: Option<String>,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style {
+            neighbourhood: Neighbourhood::Eight,
+            blanks: ".".to_string(),
+            symbols: None,
+        }
+    }
+}
+
+impl Style {
+    fn is_symbol(&self, t: char) -> bool {
+        match &self.symbols {
+            Some(s) => s.contains(t),
+            None => !self.blanks.contains(t) && !t.is_ascii_digit(),
+        }
+    }
+}
+
+// positions count chars, not bytes
+#[derive(Clone, Debug, PartialEq)]
+struct Number {
+    value: i64,
+    row: usize,
+    cols: Range<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Symbol {
+    c: char,
+    row: usize,
+    col: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Count {
+    Exactly(usize),
+    AtLeast(usize),
+}
 
-    let adjectant_to_symbol = |x: i32, y: i32| neighs.iter().map(|(xo, yo)| (xo + x, yo + y)).any(|(xi, yi)| is_symbol(get(xi, yi)));
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Aggregate {
+    Product,
+    Sum,
+    Max,
+}
+
+// which symbols count, and what they make of the numbers around them
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Rule {
+    // None for any symbol at all
+    symbol: Option<char>,
+    count: Count,
+    aggregate: Aggregate,
+}
+
+const GEAR: Rule = Rule {
+    symbol: Some('*'),
+    count: Count::Exactly(2),
+    aggregate: Aggregate::Product,
+};
 
-    let seek_to_start = |(x, y)| {
-        if !get(x, y).is_digit(10) {
-            return None;
+// e.g. "* 2 product", or "any 1+ max" for every symbol with at least one number
+impl FromStr for Rule {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        let Some((symbol, count, aggregate)) = s.split_whitespace().collect_tuple() else {
+            return Err(format!("expected symbol, count and aggregate: {s}"));
+        };
+        let symbol = match symbol {
+            "any" => None,
+            _ => Some(symbol.parse().map_err(|_| format!("not a single symbol: {symbol}"))?),
+        };
+        let count = match count.strip_suffix('+') {
+            Some(n) => Count::AtLeast(n.parse().map_err(|_| format!("bad count: {count}"))?),
+            None => Count::Exactly(count.parse().map_err(|_| format!("bad count: {count}"))?),
+        };
+        let aggregate = match aggregate {
+            "product" => Aggregate::Product,
+            "sum" => Aggregate::Sum,
+            "max" => Aggregate::Max,
+            _ => return Err(format!("no such aggregate: {aggregate}")),
         };
-        let mut xi = x;
-        while get(xi - 1, y).is_digit(10) {
-            xi -= 1;
+        Ok(Rule { symbol, count, aggregate })
+    }
+}
+
+impl Rule {
+    fn matches(&self, c: char, n: usize) -> bool {
+        let count = match self.count {
+            Count::Exactly(k) => n == k,
+            Count::AtLeast(k) => n >= k,
+        };
+        count && self.symbol.is_none_or(|s| s == c)
+    }
+
+    fn aggregate(&self, values: impl Iterator<Item = i64>) -> i64 {
+        match self.aggregate {
+            Aggregate::Product => values.product(),
+            Aggregate::Sum => values.sum(),
+            Aggregate::Max => values.max().unwrap_or(0),
         }
-        Some((xi, y))
-    };
+    }
+}
 
-    let read_from_start = |(x, y)| {
-        let mut xi = x;
-        let mut acc = 0;
-        while get(xi, y).is_digit(10) {
-            acc = add_digit(acc, get(xi, y));
-            xi += 1;
-        } // no recursion :despair:
-        acc
-    };
+type Row = (Vec<Number>, Vec<Symbol>);
 
-    let calc_gear_ratio = |x: i32, y: i32| -> i32 {
-        let gear_thingies = neighs
-            .iter()
-            .map(|(xo, yo)| (xo + x, yo + y))
-            .filter_map(seek_to_start)
-            .unique()
-            .map(read_from_start)
-            .collect_tuple();
-        match gear_thingies {
-            Some((a, b)) => a * b,
-            None => 0,
+fn parse_row(row: usize, line: &str, style: &Style) -> Result<Row, String> {
+    let mut numbers = vec![];
+    let mut symbols = vec![];
+    // col and byte offset of the number in progress
+    let mut start = None;
+    // a None past the end finishes a number at the edge
+    let chars = line.char_indices().map(|(i, c)| (i, Some(c))).chain([(line.len(), None)]);
+    for (col, (i, c)) in chars.enumerate() {
+        match (c.filter(char::is_ascii_digit), start) {
+            (Some(_), None) => start = Some((col, i)),
+            (Some(_), Some(_)) => {}
+            (None, Some((from, j))) => {
+                let digits = &line[j..i];
+                let value = digits.parse().map_err(|_| format!("row {}: {digits} doesn't fit in an i64", row + 1))?;
+                numbers.push(Number { value, row, cols: from..col });
+                start = None;
+            }
+            (None, None) => {}
         }
-    };
+        if let Some(c) = c.filter(|&c| style.is_symbol(c)) {
+            symbols.push(Symbol { c, row, col });
+        }
+    }
+    Ok((numbers, symbols))
+}
 
-    for y in 0..sizey {
-        for x in 0..=sizex {
-            let c = get(x, y);
-            if part2 {
-                if c == '*' {
-                    acc += calc_gear_ratio(x, y);
-                }
-            } else {
-                // getting over the size of array to ensure that numbers are broken
-                if c.is_digit(10) {
-                    reading = true;
-                    current = add_digit(current, c);
-                    if !current_counts {
-                        if adjectant_to_symbol(x, y) {
-                            current_counts = true;
-                        }
-                    }
-                // finish reading the number
-                } else {
-                    if reading {
-                        if current_counts {
-                            acc += current;
-                        }
-                        current_counts = false;
-                        current = 0;
-                        reading = false;
-                    }
+// everything on the schematic, and what touches what, by index into the lists
+#[derive(Debug)]
+struct Schematic {
+    numbers: Vec<Number>,
+    symbols: Vec<Symbol>,
+    // per number
+    symbols_near: Vec<Vec<usize>>,
+    // per symbol
+    numbers_near: Vec<Vec<usize>>,
+}
+
+impl Schematic {
+    fn parse(s: &str, style: &Style) -> Self {
+        let mut numbers = vec![];
+        let mut symbols = vec![];
+        for (row, line) in s.lines().enumerate() {
+            let (ns, ss) = parse_row(row, line, style).unwrap_or_else(|e| panic!("{e}"));
+            numbers.extend(ns);
+            symbols.extend(ss);
+        }
+
+        let at: HashMap<(i32, i32), usize> = symbols.iter().enumerate().map(|(i, s)| ((s.row as i32, s.col as i32), i)).collect();
+        let offsets = style.neighbourhood.offsets();
+        let mut numbers_near = vec![vec![]; symbols.len()];
+        let symbols_near = numbers
+            .iter()
+            .enumerate()
+            .map(|(i, n)| {
+                let near = n
+                    .cols
+                    .clone()
+                    .flat_map(|col| offsets.iter().map(move |(yo, xo)| (n.row as i32 + yo, col as i32 + xo)))
+                    .filter_map(|p| at.get(&p).copied())
+                    .sorted()
+                    .dedup()
+                    .collect_vec();
+                for &s in &near {
+                    numbers_near[s].push(i);
                 }
+                near
+            })
+            .collect();
+        Schematic {
+            numbers,
+            symbols,
+            symbols_near,
+            numbers_near,
+        }
+    }
+
+    fn part_numbers(&self) -> i64 {
+        self.numbers
+            .iter()
+            .zip(&self.symbols_near)
+            .filter(|(_, s)| !s.is_empty())
+            .map(|(n, _)| n.value)
+            .sum()
+    }
+
+    // summed over every symbol the rule picks
+    fn apply(&self, rule: &Rule) -> i64 {
+        self.symbols
+            .iter()
+            .zip(&self.numbers_near)
+            .filter(|(s, ns)| rule.matches(s.c, ns.len()))
+            .map(|(_, ns)| rule.aggregate(ns.iter().map(|&i| self.numbers[i].value)))
+            .sum()
+    }
+}
+
+fn touches(offsets: &[(i32, i32)], n: &Number, s: &Symbol) -> bool {
+    n.cols.clone().any(|col| {
+        offsets
+            .iter()
+            .any(|(yo, xo)| (n.row as i32 + yo, col as i32 + xo) == (s.row as i32, s.col as i32))
+    })
+}
+
+// both parts like `Schematic` gets them, but only ever holding the rows a neighbourhood spans,
+// three for the usual one. a row is done once the rows below it are in, nothing further down can touch it
+fn stream(input: impl BufRead, rule: &Rule, style: &Style) -> io::Result<(i64, i64)> {
+    let reach = style.neighbourhood.reach();
+    let offsets = style.neighbourhood.offsets();
+    let (mut part1, mut part2) = (0, 0);
+    let mut push = |window: &mut VecDeque<Row>, row: Row| {
+        window.push_back(row);
+        if window.len() < 2 * reach + 1 {
+            return;
+        }
+        let (numbers, symbols) = &window[reach];
+        for n in numbers {
+            if window.iter().flat_map(|(_, ss)| ss).any(|s| touches(&offsets, n, s)) {
+                part1 += n.value;
             }
         }
+        for s in symbols {
+            let near = window.iter().flat_map(|(ns, _)| ns).filter(|n| touches(&offsets, n, s)).collect_vec();
+            if rule.matches(s.c, near.len()) {
+                part2 += rule.aggregate(near.iter().map(|n| n.value));
+            }
+        }
+        window.pop_front();
+    };
+    // blank rows above the first and below the last
+    let mut window: VecDeque<Row> = (0..reach).map(|_| Row::default()).collect();
+    for (row, line) in input.lines().enumerate() {
+        let row = parse_row(row, &line?, style).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        push(&mut window, row);
+    }
+    for _ in 0..reach {
+        push(&mut window, Row::default());
+    }
+    Ok((part1, part2))
+}
+
+#[cfg(test)]
+fn solve(f: &str, part2: bool) -> i64 {
+    solve_str(&std::fs::read_to_string(f).unwrap(), part2)
+}
+
+#[cfg(test)]
+fn solve_str(s: &str, part2: bool) -> i64 {
+    let schematic = Schematic::parse(s, &Style::default());
+    if part2 {
+        schematic.apply(&GEAR)
+    } else {
+        schematic.part_numbers()
     }
-    acc
+}
+
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let rule = r.param("rule", GEAR);
+    let style = Style {
+        neighbourhood: r.param("neighbourhood", Neighbourhood::Eight),
+        blanks: r.param("blanks", ".".to_string()),
+        // left out or empty, anything that is neither a digit nor blank
+        symbols: Some(r.param("symbols", String::new())).filter(|s| !s.is_empty()),
+    };
+    if r.param("stream", false) {
+        let rows = r.reader(input);
+        let (part1, part2) = r.step("stream", || rows.and_then(|i| stream(i, &rule, &style)).unwrap_or_else(|e| panic!("{e}")));
+        r.part(1, || part1);
+        r.part(2, || part2);
+        return;
+    }
+    let schematic = r.parse(|| Schematic::parse(input, &style));
+    r.part(1, || schematic.part_numbers());
+    r.part(2, || schematic.apply(&rule));
 }
 
 fn main() {
-    println!("part 1: {}", solve("inputs/3b", false));
-    println!("part 2: {}", solve("inputs/3b", true));
+    let mut params = HashMap::new();
+    if std::env::args().any(|a| a == "--stream") {
+        params.insert("stream".to_string(), "true".to_string());
+    }
+    runner::main_with("inputs/3b", params, run);
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use super::*;
+    #[test]
+    fn parse_test() {
+        let s = Schematic::parse(&std::fs::read_to_string("inputs/3a").unwrap(), &Style::default());
+        assert_eq!(10, s.numbers.len());
+        assert_eq!(6, s.symbols.len());
+        assert_eq!(
+            Number {
+                value: 467,
+                row: 0,
+                cols: 0..3
+            },
+            s.numbers[0]
+        );
+        assert_eq!(Symbol { c: '*', row: 1, col: 3 }, s.symbols[0]);
+        // 467 and 35 share the first gear, 114 and 58 touch nothing
+        assert_eq!(vec![0, 2], s.numbers_near[0]);
+        assert_eq!(vec![vec![0], vec![], vec![0]], s.symbols_near[..3]);
+        assert!(s.symbols_near[5].is_empty());
+        let edge = Schematic::parse("12\n.#\n..3", &Style::default());
+        // cols count chars, the number is still read off of the right bytes
+        let wide = Schematic::parse("é12#\n9999999999", &Style::default());
+        assert_eq!(1..3, wide.numbers[0].cols);
+        assert_eq!(vec![12, 9999999999], wide.numbers.iter().map(|n| n.value).collect_vec());
+        assert_eq!(vec![12, 3], edge.numbers.iter().map(|n| n.value).collect_vec());
+        assert_eq!(vec![vec![0], vec![0]], edge.symbols_near);
+    }
+    #[test]
+    fn rule_test() {
+        let s = Schematic::parse(&std::fs::read_to_string("inputs/3a").unwrap(), &Style::default());
+        assert_eq!(Ok(GEAR), "* 2 product".parse());
+        assert_eq!(467835, s.apply(&GEAR));
+        // the lone gear next to 617 counts too
+        assert_eq!(467 + 35 + 617 + 755 + 598, s.apply(&"* 1+ sum".parse().unwrap()));
+        assert_eq!(617, s.apply(&"* 1 max".parse().unwrap()));
+        assert_eq!(4361, s.apply(&"any 1+ sum".parse().unwrap()));
+        assert_eq!(633 + 617 + 592 + 664, s.apply(&"any 1 max".parse().unwrap()));
+        assert_eq!(0, s.apply(&"* 3+ product".parse().unwrap()));
+        assert!("* x sum".parse::<Rule>().is_err());
+        assert!("** 2 sum".parse::<Rule>().is_err());
+        assert!("* 2 min".parse::<Rule>().is_err());
+    }
+    #[test]
+    fn stream_test() {
+        let rules = [
+            GEAR,
+            "any 1+ sum".parse().unwrap(),
+            "* 1 max".parse().unwrap(),
+            "# 0+ product".parse().unwrap(),
+        ];
+        for f in ["inputs/3a", "inputs/3b"] {
+            let input = std::fs::read_to_string(f).unwrap();
+            let s = Schematic::parse(&input, &Style::default());
+            for rule in &rules {
+                assert_eq!(
+                    (s.part_numbers(), s.apply(rule)),
+                    stream(input.as_bytes(), rule, &Style::default()).unwrap(),
+                    "{f} {rule:?}"
+                );
+            }
+        }
+        assert_eq!((3, 3), stream("3*".as_bytes(), &"* 1 sum".parse().unwrap(), &Style::default()).unwrap());
+        assert_eq!((0, 0), stream("".as_bytes(), &GEAR, &Style::default()).unwrap());
+        // not utf-8, so the line can't be read
+        assert!(stream(&b"1*\n\xff\n"[..], &GEAR, &Style::default()).is_err());
+        // too long for an i64
+        let long = "99999999999999999999*";
+        let e = stream(long.as_bytes(), &GEAR, &Style::default()).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, e.kind());
+        let mut r = Runner::new();
+        assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| run(long, &mut r))).is_err());
+        assert_eq!(Some("parse".to_string()), r.running);
+        let mut r = Runner::new();
+        r.params.insert("stream".to_string(), "true".to_string());
+        run(&std::fs::read_to_string("inputs/3a").unwrap(), &mut r);
+        assert_eq!((Some("4361"), Some("467835")), (r.answer(1), r.answer(2)));
+        // straight from the file, like the binary does
+        let mut r = Runner::new();
+        r.params.insert("stream".to_string(), "true".to_string());
+        r.path = Some("inputs/3a".to_string());
+        run("", &mut r);
+        assert_eq!((Some("4361"), Some("467835")), (r.answer(1), r.answer(2)));
+        let below = stream("12\n.#\n".as_bytes(), &"# 1 sum".parse().unwrap(), &Style::default());
+        assert_eq!((12, 12), below.unwrap());
+    }
+    #[test]
+    fn style_param_test() {
+        let mut r = Runner::new();
+        r.params.insert("symbols".to_string(), "+".to_string());
+        run("1....\n.*2..\n...+.\n.3_4.", &mut r);
+        assert_eq!(Some("6"), r.answer(1));
+        r = Runner::new();
+        r.params.insert("symbols".to_string(), String::new());
+        run("1....\n.*2..\n...+.\n.3_4.", &mut r);
+        assert_eq!(Some("10"), r.answer(1));
+    }
+    #[test]
+    fn style_test() {
+        assert_eq!(4, Neighbourhood::Four.offsets().len());
+        assert_eq!(Neighbourhood::Radius(1).offsets(), Neighbourhood::Eight.offsets());
+        assert_eq!(24, "r2".parse::<Neighbourhood>().unwrap().offsets().len());
+        assert!("r".parse::<Neighbourhood>().is_err());
+
+        let input = "1....\n.*2..\n...+.\n.3_4.";
+        let part1 = |style: &Style| Schematic::parse(input, style).part_numbers();
+        let mut style = Style::default();
+        assert_eq!(1 + 2 + 3 + 4, part1(&style));
+        style.neighbourhood = Neighbourhood::Four;
+        assert_eq!(2 + 3 + 4, part1(&style));
+        // with '_' blank, 3 has nothing around it
+        style.neighbourhood = Neighbourhood::Eight;
+        style.blanks = "._".to_string();
+        assert_eq!(1 + 2 + 4, part1(&style));
+        style.symbols = Some("+".to_string());
+        assert_eq!(2 + 4, part1(&style));
+        style.neighbourhood = Neighbourhood::Radius(2);
+        assert_eq!(2 + 3 + 4, part1(&style));
+
+        for style in [
+            Style::default(),
+            style.clone(),
+            Style {
+                symbols: None,
+                ..style.clone()
+            },
+        ] {
+            for rule in [GEAR, "any 1+ sum".parse().unwrap(), "any 2+ product".parse().unwrap()] {
+                for input in [input.to_string(), std::fs::read_to_string("inputs/3a").unwrap()] {
+                    let s = Schematic::parse(&input, &style);
+                    assert_eq!(
+                        (s.part_numbers(), s.apply(&rule)),
+                        stream(input.as_bytes(), &rule, &style).unwrap(),
+                        "{style:?} {rule:?}"
+                    );
+                }
+            }
+        }
+    }
     #[test]
     fn part1() {
         assert_eq!(4361, solve("inputs/3a", false))
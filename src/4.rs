@@ -1,6 +1,10 @@
-use std::fs::read_to_string;
-
+use aoc2023::json::Json;
+use aoc2023::runner::{self, Runner};
 use itertools::Itertools;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::io::{self, BufRead};
+use std::str::FromStr;
 
 #[derive(Clone, Debug, PartialEq)]
 struct Line {
@@ -9,23 +13,155 @@ struct Line {
     right: Vec<usize>,
 }
 
-fn parse_subline(l: &str) -> Vec<usize> {
-    l.split_whitespace().filter_map(|x| x.parse::<usize>().ok()).collect()
+fn parse_subline(l: &str) -> Result<Vec<usize>, String> {
+    l.split_whitespace()
+        .map(|x| x.parse::<usize>().map_err(|_| format!("{x} is not a number")))
+        .collect()
+}
+
+fn parse_line(l: &str) -> Result<Line, String> {
+    let (head, rest) = l.split(':').collect_tuple().ok_or("expected one ':'")?;
+    let (left, right) = rest.split('|').map(parse_subline).collect_tuple().ok_or("expected one '|'")?;
+    let id = head.split_whitespace().last().and_then(|id| id.parse().ok());
+    let (id, left, right) = (id.ok_or(format!("no card id in {head:?}"))?, left?, right?);
+    Ok(Line { id, left, right })
 }
 
-fn parse_line(l: &str) -> Line {
-    let (head, rest) = l.split(':').collect_tuple().unwrap();
-    let (left, right) = rest.split('|').map(parse_subline).collect_tuple().unwrap();
-    let id = head.split_whitespace().last().unwrap().parse::<usize>().unwrap();
-    Line { id, left, right }
+fn parse_str(s: &str) -> Result<Vec<Line>, String> {
+    let line = |(i, l): (usize, &str)| parse_line(l).map_err(|e| format!("line {}: {e}", i + 1));
+    s.lines().enumerate().map(line).collect()
 }
 
+// a bit per number, meant for small ones like on the cards
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Bits(Vec<u64>);
+
+impl Bits {
+    fn insert(&mut self, n: usize) {
+        if n / 64 >= self.0.len() {
+            self.0.resize(n / 64 + 1, 0);
+        }
+        self.0[n / 64] |= 1 << (n % 64);
+    }
+
+    fn contains(&self, n: usize) -> bool {
+        self.0.get(n / 64).is_some_and(|w| w >> (n % 64) & 1 == 1)
+    }
+}
+
+impl FromIterator<usize> for Bits {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut bits = Bits::default();
+        iter.into_iter().for_each(|n| bits.insert(n));
+        bits
+    }
+}
+
+// past this a card's winning numbers go in a sorted list, a bit per number would be
+// more memory than the card is worth
+const MAX_BIT: usize = 1 << 16;
+
 fn calc_line_matches(l: Line) -> usize {
-    l.right.iter().filter(|n| l.left.contains(n)).count()
+    if l.left.iter().all(|&n| n < MAX_BIT) {
+        let winning: Bits = l.left.iter().copied().collect();
+        l.right.iter().filter(|&&n| winning.contains(n)).count()
+    } else {
+        let winning = l.left.iter().copied().sorted().collect_vec();
+        l.right.iter().filter(|n| winning.binary_search(n).is_ok()).count()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Anomaly {
+    Duplicate { winning: bool, number: usize },
+    // compared to the first card
+    Lengths { found: (usize, usize), expected: (usize, usize) },
+    // the card before had another id
+    Id { expected: usize },
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Anomaly::Duplicate { winning: true, number } => write!(f, "winning number {number} appears more than once"),
+            Anomaly::Duplicate { winning: false, number } => write!(f, "number {number} appears more than once"),
+            Anomaly::Lengths { found, expected } => {
+                write!(f, "has {} | {} numbers instead of {} | {}", found.0, found.1, expected.0, expected.1)
+            }
+            Anomaly::Id { expected } => write!(f, "should be card {expected}"),
+        }
+    }
+}
+
+// odd cards along with what's odd about them
+type Anomalies = Vec<(usize, Vec<Anomaly>)>;
+
+// what's odd about one card, given the lengths of the first card and the id of the one before
+fn check(l: &Line, expected: (usize, usize), prev: Option<usize>) -> Vec<Anomaly> {
+    let mut found = vec![];
+    for (winning, numbers) in [(true, &l.left), (false, &l.right)] {
+        let dups = numbers.iter().duplicates().sorted();
+        found.extend(dups.map(|&number| Anomaly::Duplicate { winning, number }));
+    }
+    if (l.left.len(), l.right.len()) != expected {
+        let found_ = (l.left.len(), l.right.len());
+        found.push(Anomaly::Lengths { found: found_, expected });
+    }
+    if let Some(prev) = prev.filter(|&p| l.id != p + 1) {
+        found.push(Anomaly::Id { expected: prev + 1 });
+    }
+    found
+}
+
+// cards with anything odd about them, by position
+fn validate(lines: &[Line]) -> Anomalies {
+    let Some(first) = lines.first() else {
+        return vec![];
+    };
+    let expected = (first.left.len(), first.right.len());
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, l)| (i, check(l, expected, i.checked_sub(1).map(|j| lines[j].id))))
+        .filter(|(_, found)| !found.is_empty())
+        .collect()
+}
+
+// a note for every odd card, and whether the ids went up one by one
+fn note_anomalies<'a>(r: &mut Runner, by_id: impl Iterator<Item = (usize, &'a Vec<Anomaly>)>) -> bool {
+    let mut contiguous = true;
+    for (id, found) in by_id {
+        contiguous &= !found.iter().any(|a| matches!(a, Anomaly::Id { .. }));
+        r.note(&format!("card {id}"), found.iter().join(", "));
+    }
+    contiguous
 }
 
-fn line_scores(f: &str) -> Vec<usize> {
-    read_to_string(f).unwrap().lines().map(parse_line).map(calc_line_matches).collect()
+// what part 2 does when card ids skip or repeat, copies go by position either way
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Ids {
+    Refuse,
+    Warn,
+}
+
+impl FromStr for Ids {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "refuse" => Ok(Ids::Refuse),
+            "warn" => Ok(Ids::Warn),
+            _ => Err(format!("no such policy: {s}")),
+        }
+    }
+}
+
+fn line_scores(lines: Vec<Line>) -> Vec<usize> {
+    lines.into_iter().map(calc_line_matches).collect()
+}
+
+#[cfg(test)]
+fn parse(f: &str) -> Vec<Line> {
+    parse_str(&std::fs::read_to_string(f).unwrap()).unwrap()
 }
 
 fn calc_line_points(i: usize) -> usize {
@@ -35,12 +171,16 @@ fn calc_line_points(i: usize) -> usize {
     }
 }
 
+fn points(scores: &[usize]) -> usize {
+    scores.iter().copied().map(calc_line_points).sum()
+}
+
+#[cfg(test)]
 fn solve(f: &str) -> usize {
-    line_scores(f).iter().copied().map(calc_line_points).sum()
+    points(&line_scores(parse(f)))
 }
 
-fn solve2(f: &str) -> usize {
-    let scores = line_scores(f);
+fn cards(scores: &[usize]) -> usize {
     let mut counts = scores.iter().map(|_| 1).collect_vec();
     for i in 0..counts.len() {
         let s = scores[i];
@@ -53,14 +193,207 @@ fn solve2(f: &str) -> usize {
     counts.iter().sum()
 }
 
+// where every copy of every card came from, cards by position
+#[derive(Debug, PartialEq)]
+struct Cascade {
+    // the original included
+    copies: Vec<usize>,
+    // earlier card and how many copies it handed over
+    from: Vec<Vec<(usize, usize)>>,
+}
+
+fn cascade(scores: &[usize]) -> Cascade {
+    let mut copies = scores.iter().map(|_| 1).collect_vec();
+    let mut from = scores.iter().map(|_| vec![]).collect_vec();
+    for i in 0..copies.len() {
+        for j in i + 1..(i + scores[i] + 1) {
+            copies[j] += copies[i];
+            from[j].push((i, copies[i]));
+        }
+    }
+    Cascade { copies, from }
+}
+
+impl Cascade {
+    // e.g. "4 copies: 1 original, 1 from card 1, 2 from card 2"
+    fn explain(&self, i: usize) -> String {
+        let from = self.from[i].iter().map(|(j, n)| format!(", {n} from card {}", j + 1)).join("");
+        format!("{} copies: 1 original{from}", self.copies[i])
+    }
+}
+
+// the original of card `i` and every copy it goes on to win, as a tree of single copies,
+// cut off below `depth`. `copies` counts the whole subtree, cut or not
+fn tree(scores: &[usize], i: usize, depth: usize) -> Json {
+    let mut size = vec![1; scores.len()];
+    for j in (0..scores.len()).rev() {
+        size[j] += (j + 1..j + scores[j] + 1).map(|k| size[k]).sum::<usize>();
+    }
+    fn node(scores: &[usize], size: &[usize], i: usize, depth: usize) -> Json {
+        let mut o = vec![("card".to_string(), Json::from(i + 1)), ("copies".to_string(), Json::from(size[i]))];
+        if depth > 0 && scores[i] > 0 {
+            let won = (i + 1..i + scores[i] + 1).map(|j| node(scores, size, j, depth - 1)).collect();
+            o.push(("won".to_string(), Json::Array(won)));
+        }
+        Json::Object(o)
+    }
+    node(scores, &size, i, depth)
+}
+
+#[cfg(test)]
+fn solve2(f: &str) -> usize {
+    cards(&line_scores(parse(f)))
+}
+
+// `cards` without the table, only the copies still owed to the cards ahead are kept,
+// so never more than the most matches on one card
+fn stream_cards(scores: impl Iterator<Item = usize>) -> usize {
+    let mut owed = VecDeque::new();
+    let mut total = 0;
+    for s in scores {
+        let c = 1 + owed.pop_front().unwrap_or(0);
+        total += c;
+        if owed.len() < s {
+            owed.resize(s, 0);
+        }
+        owed.iter_mut().take(s).for_each(|o| *o += c);
+    }
+    total
+}
+
+// both parts in one pass over the cards, and the odd cards by id, checked like `validate` does.
+// a card that doesn't parse is invalid data, same as a line that isn't utf-8
+fn stream(input: impl BufRead) -> io::Result<(usize, usize, Anomalies)> {
+    let mut part1 = 0;
+    let mut first = None;
+    let mut last = None;
+    let mut anomalies = vec![];
+    let mut error = None;
+    let lines = input.lines().enumerate().map_while(|(i, l)| {
+        let invalid = |e| io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {e}", i + 1));
+        let l = l.and_then(|l| parse_line(&l).map_err(invalid));
+        l.map_err(|e| error = Some(e)).ok()
+    });
+    let scores = lines.map(|l| {
+        let expected = *first.get_or_insert((l.left.len(), l.right.len()));
+        let found = check(&l, expected, last);
+        if !found.is_empty() {
+            anomalies.push((l.id, found));
+        }
+        last = Some(l.id);
+        let s = calc_line_matches(l);
+        part1 += calc_line_points(s);
+        s
+    });
+    let part2 = stream_cards(scores);
+    match error {
+        Some(e) => Err(e),
+        None => Ok((part1, part2, anomalies)),
+    }
+}
+
+// copies won past the last card are lost instead of being an error
+fn bounded_cards(scores: &[usize]) -> usize {
+    let mut counts = scores.iter().map(|_| 1).collect_vec();
+    for i in 0..counts.len() {
+        for j in i + 1..(i + scores[i] + 1).min(counts.len()) {
+            counts[j] += counts[i];
+        }
+    }
+    counts.iter().sum()
+}
+
+// what a table of match counts is worth
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Scoring {
+    // part 1
+    Doubling,
+    // a point per match
+    Linear,
+    // part 2
+    Copies,
+    // copies, minus the ones past the last card
+    Bounded,
+}
+
+impl FromStr for Scoring {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "doubling" => Ok(Scoring::Doubling),
+            "linear" => Ok(Scoring::Linear),
+            "copies" => Ok(Scoring::Copies),
+            "bounded" => Ok(Scoring::Bounded),
+            _ => Err(format!("no such scoring: {s}")),
+        }
+    }
+}
+
+impl Scoring {
+    fn score(self, scores: &[usize]) -> usize {
+        match self {
+            Scoring::Doubling => points(scores),
+            Scoring::Linear => scores.iter().sum(),
+            Scoring::Copies => cards(scores),
+            Scoring::Bounded => bounded_cards(scores),
+        }
+    }
+}
+
+// copies go by position, so part 2 only goes ahead on skipped or repeated ids when `ids` says so
+fn part2_by_position(r: &mut Runner, ids: Ids, contiguous: bool, f: impl FnOnce() -> usize) {
+    r.part(2, || {
+        assert!(contiguous || ids == Ids::Warn, "card ids are not contiguous");
+        f()
+    });
+    if !contiguous && ids == Ids::Warn && r.answer(2).is_some() {
+        r.note("warning", "card ids are not contiguous, copies went by position");
+    }
+}
+
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let ids = r.param("ids", Ids::Refuse);
+    // the default scoring, without holding on to anything
+    if r.param("stream", false) {
+        let cards = r.reader(input);
+        let (part1, part2, anomalies) = r.step("stream", || cards.and_then(stream).unwrap_or_else(|e| panic!("{e}")));
+        let contiguous = note_anomalies(r, anomalies.iter().map(|(id, found)| (*id, found)));
+        r.part(1, || part1);
+        part2_by_position(r, ids, contiguous, || part2);
+        return;
+    }
+    let lines = r.parse(|| parse_str(input).unwrap_or_else(|e| panic!("{e}")));
+    let anomalies = r.step("validate", || validate(&lines));
+    let contiguous = note_anomalies(r, anomalies.iter().map(|(i, found)| (lines[*i].id, found)));
+    let scores = r.step("matches", || line_scores(lines));
+    let (p1, p2) = (r.param("part1", Scoring::Doubling), r.param("part2", Scoring::Copies));
+    r.part(1, || p1.score(&scores));
+    part2_by_position(r, ids, contiguous, || p2.score(&scores));
+    // a card number, to see how it got its copies
+    let explain: usize = r.param("explain", 0);
+    if explain > 0 {
+        let c = r.step("cascade", || {
+            let n = scores.len();
+            assert!(explain <= n, "bad value for param explain: {explain}, there are {n} cards");
+            cascade(&scores)
+        });
+        r.note(&format!("card {explain}"), c.explain(explain - 1));
+        let depth = r.param("depth", 3);
+        r.note(&format!("card {explain} tree"), tree(&scores, explain - 1, depth));
+    }
+}
+
 fn main() {
-    println!("part 1: {}", solve("inputs/4b"));
-    println!("part 2: {}", solve2("inputs/4b"));
+    let mut params = HashMap::new();
+    if std::env::args().any(|a| a == "--stream") {
+        params.insert("stream".to_string(), "true".to_string());
+    }
+    runner::main_with("inputs/4b", params, run);
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use super::*;
     #[test]
     fn parsing() {
         let expected = Line {
@@ -69,7 +402,17 @@ mod tests {
             right: vec![83, 86, 6, 31, 17, 9, 48, 53],
         };
         let input = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53";
-        assert_eq!(expected, parse_line(input));
+        assert_eq!(Ok(expected), parse_line(input));
+        assert_eq!(Err("x is not a number".to_string()), parse_line("Card 1: 1 | x"));
+        assert!(parse_line("Card 1: 1 2").is_err());
+        assert!(parse_line("Card: 1 | 2").is_err());
+        assert_eq!(Err("line 2: x is not a number".to_string()), parse_str("Card 1: 1 | 2\nCard 2: x | 1"));
+
+        let bad = "Card 1: 1 | x";
+        let mut r = Runner::new();
+        assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| run(bad, &mut r))).is_err());
+        assert_eq!(Some("parse".to_string()), r.running);
+        assert_eq!(io::ErrorKind::InvalidData, stream(bad.as_bytes()).unwrap_err().kind());
     }
     #[test]
     fn point_calculation() {
@@ -85,10 +428,121 @@ mod tests {
         };
         assert_eq!(4, calc_line_matches(l1));
         assert_eq!(0, calc_line_matches(l2));
+        // too big for bits, that would be a huge allocation
+        let huge = parse_line("Card 1: 18446744073709551615 7 | 7 18446744073709551615 8").unwrap();
+        assert_eq!(2, calc_line_matches(huge));
         assert_eq!(8, calc_line_points(4));
         assert_eq!(0, calc_line_points(0));
     }
     #[test]
+    fn cascade_test() {
+        let scores = line_scores(parse("inputs/4a"));
+        let c = cascade(&scores);
+        assert_eq!(vec![1, 2, 4, 8, 14, 1], c.copies);
+        assert_eq!(vec![(0, 1), (2, 4), (3, 8)], c.from[4]);
+        assert_eq!("4 copies: 1 original, 1 from card 1, 2 from card 2", c.explain(2));
+        assert_eq!("1 copies: 1 original", c.explain(5));
+        assert_eq!(cards(&scores), c.copies.iter().sum());
+        // every copy is in exactly one original's tree
+        let size = |i| tree(&scores, i, 0).get("copies").and_then(Json::as_usize).unwrap();
+        assert_eq!(cards(&scores), (0..scores.len()).map(size).sum());
+        assert_eq!(
+            r#"{"card":3,"copies":4,"won":[{"card":4,"copies":2,"won":[{"card":5,"copies":1}]},{"card":5,"copies":1}]}"#,
+            tree(&scores, 2, 5).to_string()
+        );
+        assert_eq!(r#"{"card":3,"copies":4}"#, tree(&scores, 2, 0).to_string());
+
+        let mut r = Runner::new();
+        r.params.insert("explain".to_string(), "7".to_string());
+        let input = std::fs::read_to_string("inputs/4a").unwrap();
+        assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| run(&input, &mut r))).is_err());
+        assert_eq!((Some("13"), Some("30")), (r.answer(1), r.answer(2)));
+        assert_eq!(Some("cascade".to_string()), r.running);
+    }
+    #[test]
+    fn scoring_test() {
+        let scores = line_scores(parse("inputs/4a"));
+        assert_eq!(13, Scoring::Doubling.score(&scores));
+        assert_eq!(9, Scoring::Linear.score(&scores));
+        assert_eq!(30, Scoring::Copies.score(&scores));
+        assert_eq!(30, Scoring::Bounded.score(&scores));
+        // the last card would win copies of cards that aren't there
+        assert_eq!(1 + 2 + 4, Scoring::Bounded.score(&[2, 1, 3]));
+        assert_eq!(Ok(Scoring::Bounded), "bounded".parse());
+        assert!("tripling".parse::<Scoring>().is_err());
+
+        let mut r = Runner::new();
+        r.params.insert("part1".to_string(), "linear".to_string());
+        r.params.insert("part2".to_string(), "doubling".to_string());
+        run(&std::fs::read_to_string("inputs/4a").unwrap(), &mut r);
+        assert_eq!((Some("9"), Some("13")), (r.answer(1), r.answer(2)));
+    }
+    #[test]
+    fn bits_test() {
+        let bits: Bits = [3, 64, 200].into_iter().collect();
+        assert!(bits.contains(3) && bits.contains(64) && bits.contains(200));
+        assert!(!bits.contains(4) && !bits.contains(0) && !bits.contains(1000));
+        assert_eq!(4, bits.0.len());
+    }
+    #[test]
+    fn stream_test() {
+        for f in ["inputs/4a", "inputs/4b"] {
+            let input = std::fs::read_to_string(f).unwrap();
+            let scores = line_scores(parse(f));
+            assert_eq!((points(&scores), cards(&scores), vec![]), stream(input.as_bytes()).unwrap());
+        }
+        assert_eq!(7, stream_cards([2, 1, 3].into_iter()));
+        assert_eq!(300_000, stream_cards([1, 0].into_iter().cycle().take(200_000)));
+
+        let mut r = Runner::new();
+        r.params.insert("stream".to_string(), "true".to_string());
+        run(&std::fs::read_to_string("inputs/4a").unwrap(), &mut r);
+        assert_eq!((Some("13"), Some("30")), (r.answer(1), r.answer(2)));
+        // straight from the file, like the binary does
+        let mut r = Runner::new();
+        r.params.insert("stream".to_string(), "true".to_string());
+        r.path = Some("inputs/4a".to_string());
+        run("", &mut r);
+        assert_eq!((Some("13"), Some("30")), (r.answer(1), r.answer(2)));
+    }
+    #[test]
+    fn validate_test() {
+        let input = "Card 1: 1 2 3 | 4 5 6 7\nCard 2: 1 1 2 | 2 2 9 9\nCard 4: 1 2 | 1 3 4 5\nCard 5: 7 8 9 | 1 2 3 4";
+        let found = validate(&parse_str(input).unwrap());
+        assert_eq!(vec![1, 2], found.iter().map(|(i, _)| *i).collect_vec());
+        assert_eq!(
+            vec![
+                Anomaly::Duplicate { winning: true, number: 1 },
+                Anomaly::Duplicate { winning: false, number: 2 },
+                Anomaly::Duplicate { winning: false, number: 9 },
+            ],
+            found[0].1
+        );
+        assert!(validate(&[]).is_empty());
+
+        let card4 = ("card 4".to_string(), "has 2 | 4 numbers instead of 3 | 4, should be card 3".to_string());
+        // streaming or not, the same notes and only part 2 refused
+        for stream in ["false", "true"] {
+            let mut r = Runner::new();
+            r.params.insert("stream".to_string(), stream.to_string());
+            assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| run(input, &mut r))).is_err());
+            assert_eq!((Some("3"), None), (r.answer(1), r.answer(2)));
+            assert_eq!(Some("part 2".to_string()), r.running);
+            assert_eq!(2, r.notes.len());
+            assert_eq!(card4, r.notes[1]);
+        }
+
+        let warning = ("warning".to_string(), "card ids are not contiguous, copies went by position".to_string());
+        for stream in ["false", "true"] {
+            let mut r = Runner::new();
+            r.params.insert("ids".to_string(), "warn".to_string());
+            r.params.insert("stream".to_string(), stream.to_string());
+            run(input, &mut r);
+            assert_eq!((Some("3"), Some("8")), (r.answer(1), r.answer(2)));
+            assert_eq!(Some(&warning), r.notes.last());
+        }
+    }
+    #[test]
     fn part1() {
         assert_eq!(13, solve("inputs/4a"));
     }
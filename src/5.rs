@@ -1,6 +1,6 @@
-use std::fs::read_to_string;
-
+use aoc2023::runner::{self, Runner};
 use itertools::Itertools;
+use std::fmt;
 
 fn parse_line_of_numbers(l: &str) -> impl Iterator<Item = i64> + '_ {
     l.split_whitespace().filter_map(|x| x.parse::<i64>().ok())
@@ -80,53 +80,348 @@ fn translate_ranges(rs: Vec<Range>, ms: Vec<MapLine>) -> Vec<Range> {
     result
 }
 
-fn parse_mapping(f: &str) -> MapLine {
-    let (dest, src, len) = parse_line_of_numbers(f).collect_tuple().unwrap();
-    MapLine { dest, src, len }
+// values in start..end move by offset
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Segment {
+    start: i64,
+    end: i64,
+    offset: i64,
+}
+
+// a whole almanac or a part of it as one function, segments are sorted, don't overlap and
+// cover every i64 (but i64::MAX), neighbours never share an offset
+#[derive(Clone, Debug, PartialEq)]
+struct Piecewise(Vec<Segment>);
+
+impl Piecewise {
+    fn identity() -> Self {
+        Piecewise(vec![Segment {
+            start: i64::MIN,
+            end: i64::MAX,
+            offset: 0,
+        }])
+    }
+
+    fn normalized(segments: impl IntoIterator<Item = Segment>) -> Self {
+        let mut v: Vec<Segment> = vec![];
+        for s in segments.into_iter().filter(|s| s.start < s.end) {
+            match v.last_mut() {
+                Some(last) if last.offset == s.offset && last.end == s.start => last.end = s.end,
+                _ => v.push(s),
+            }
+        }
+        Piecewise(v)
+    }
+
+    // earlier lines win where lines overlap, like in `translate_ranges`
+    fn layer(ms: &[MapLine]) -> Self {
+        let cuts = ms
+            .iter()
+            .flat_map(|m| [m.src, m.src + m.len])
+            .chain([i64::MIN, i64::MAX])
+            .sorted()
+            .dedup()
+            .collect_vec();
+        Self::normalized(cuts.into_iter().tuple_windows().map(|(start, end)| {
+            let line = ms.iter().find(|m| m.src <= start && start < m.src + m.len);
+            Segment {
+                start,
+                end,
+                offset: line.map_or(0, |m| m.dest - m.src),
+            }
+        }))
+    }
+
+    // index of the segment holding x
+    fn find(&self, x: i64) -> usize {
+        self.0.partition_point(|s| s.end <= x)
+    }
+
+    // `self` first, then `next`. the outer segments never move, so nothing overflows
+    fn then(&self, next: &Piecewise) -> Piecewise {
+        Self::normalized(self.0.iter().flat_map(|s| {
+            let (lo, hi) = (s.start + s.offset, s.end + s.offset);
+            next.0[next.find(lo)..].iter().take_while(move |n| n.start < hi).map(move |n| Segment {
+                start: lo.max(n.start) - s.offset,
+                end: hi.min(n.end) - s.offset,
+                offset: s.offset + n.offset,
+            })
+        }))
+    }
+
+    // None when some values share an image, or some have none
+    fn inverse(&self) -> Option<Piecewise> {
+        let image = self
+            .0
+            .iter()
+            .map(|s| Segment {
+                start: s.start + s.offset,
+                end: s.end + s.offset,
+                offset: -s.offset,
+            })
+            .sorted_by_key(|s| s.start)
+            .collect_vec();
+        image
+            .iter()
+            .tuple_windows()
+            .all(|(a, b)| a.end == b.start)
+            .then(|| Self::normalized(image))
+    }
+
+    fn apply(&self, x: i64) -> i64 {
+        x + self.0[self.find(x)].offset
+    }
+
+    // sorts `rs` so the segments are only walked once
+    fn apply_ranges(&self, mut rs: Vec<Range>) -> Vec<Range> {
+        rs.sort_by_key(|r| r.start);
+        let mut i = 0;
+        let mut result = vec![];
+        for r in rs.into_iter().filter(|r| r.len > 0) {
+            let end = r.start + r.len;
+            while self.0[i].end <= r.start {
+                i += 1;
+            }
+            for s in self.0[i..].iter().take_while(|s| s.start < end) {
+                let start = r.start.max(s.start);
+                result.push(Range {
+                    start: start + s.offset,
+                    len: end.min(s.end) - start,
+                });
+            }
+        }
+        result
+    }
+}
+
+fn compose<'a>(mappings: impl IntoIterator<Item = &'a Vec<MapLine>>) -> Piecewise {
+    mappings
+        .into_iter()
+        .fold(Piecewise::identity(), |acc, ms| acc.then(&Piecewise::layer(ms)))
+}
+
+// one section, along with the categories named in its header
+#[derive(Clone, Debug, PartialEq)]
+struct Layer {
+    from: String,
+    to: String,
+    lines: Vec<MapLine>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum RouteError {
+    Unknown(String),
+    NoRoute(String, String),
+    Ambiguous(String, String),
+}
+
+impl fmt::Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RouteError::Unknown(c) => write!(f, "no category named {c}"),
+            RouteError::NoRoute(from, to) => write!(f, "no route from {from} to {to}"),
+            RouteError::Ambiguous(from, to) => write!(f, "more than one route from {from} to {to}"),
+        }
+    }
+}
+
+// the layers that lead from one category to another, in order. an almanac that
+// branches is fine, as long as only one way leads there
+fn route(layers: &[Layer], from: &str, to: &str) -> Result<Vec<usize>, RouteError> {
+    for c in [from, to] {
+        if !layers.iter().any(|l| l.from == c || l.to == c) {
+            return Err(RouteError::Unknown(c.to_string()));
+        }
+    }
+    // never through the same category twice, and stops looking after the second route
+    fn walk<'a>(layers: &'a [Layer], to: &str, path: &mut Vec<usize>, seen: &mut Vec<&'a str>, found: &mut Vec<Vec<usize>>) {
+        if *seen.last().unwrap() == to {
+            found.push(path.clone());
+            return;
+        }
+        for (i, l) in layers.iter().enumerate() {
+            if l.from == *seen.last().unwrap() && !seen.contains(&l.to.as_str()) && found.len() < 2 {
+                path.push(i);
+                seen.push(&l.to);
+                walk(layers, to, path, seen, found);
+                seen.pop();
+                path.pop();
+            }
+        }
+    }
+    let mut found = vec![];
+    walk(layers, to, &mut vec![], &mut vec![from], &mut found);
+    match found.len() {
+        0 => Err(RouteError::NoRoute(from.to_string(), to.to_string())),
+        1 => Ok(found.pop().unwrap()),
+        _ => Err(RouteError::Ambiguous(from.to_string(), to.to_string())),
+    }
+}
+
+fn translation(layers: &[Layer], from: &str, to: &str) -> Result<Piecewise, RouteError> {
+    Ok(compose(route(layers, from, to)?.into_iter().map(|i| &layers[i].lines)))
+}
+
+// merges ranges that overlap or touch, the result is sorted
+fn coalesce(mut rs: Vec<Range>) -> Vec<Range> {
+    rs.sort_by_key(|r| r.start);
+    let mut result: Vec<Range> = vec![];
+    for r in rs.into_iter().filter(|r| r.len > 0) {
+        match result.last_mut() {
+            Some(last) if r.start <= last.start + last.len => last.len = last.len.max(r.start + r.len - last.start),
+            _ => result.push(r),
+        }
+    }
+    result
+}
+
+// two lines of one layer that both claim start..end, lines by position in the section
+#[derive(Clone, Debug, PartialEq)]
+struct Overlap {
+    layer: usize,
+    lines: (usize, usize),
+    start: i64,
+    end: i64,
+}
+
+fn overlaps(layers: &[Layer]) -> Vec<Overlap> {
+    layers
+        .iter()
+        .enumerate()
+        .flat_map(|(layer, l)| {
+            l.lines.iter().enumerate().tuple_combinations().filter_map(move |((i, a), (j, b))| {
+                let (start, end) = (a.src.max(b.src), (a.src + a.len).min(b.src + b.len));
+                (start < end).then_some(Overlap {
+                    layer,
+                    lines: (i, j),
+                    start,
+                    end,
+                })
+            })
+        })
+        .collect()
+}
+
+fn parse_mapping(f: &str) -> Result<MapLine, String> {
+    let (dest, src, len) = parse_line_of_numbers(f).collect_tuple().ok_or(format!("expected three numbers: {f}"))?;
+    // the ends and the offset get computed all over the place, so they have to fit
+    if src.checked_add(len).is_none() || dest.checked_add(len).is_none() || dest.checked_sub(src).is_none() {
+        return Err(format!("line doesn't fit in an i64: {f}"));
+    }
+    Ok(MapLine { dest, src, len })
 }
 
-fn parse_section(f: &str) -> Vec<MapLine> {
+fn parse_section(f: &str) -> Result<Vec<MapLine>, String> {
     f.split("\n").skip(1).filter(|x| x.len() > 0).map(parse_mapping).collect()
 }
 
+#[cfg(test)]
 fn parse(f: &str) -> (Vec<i64>, Vec<Vec<MapLine>>) {
-    let s = read_to_string(f).unwrap();
-    let mut i = s.split("\n\n");
-    let seeds = parse_line_of_numbers(i.next().unwrap()).collect();
-    let mappings = i.map(parse_section).collect();
+    parse_str(&std::fs::read_to_string(f).unwrap())
+}
+
+#[cfg(test)]
+fn parse_str(s: &str) -> (Vec<i64>, Vec<Vec<MapLine>>) {
+    let (seeds, layers) = parse_almanac(s).unwrap();
+    (seeds, layers.into_iter().map(|l| l.lines).collect())
+}
 
-    (seeds, mappings)
+// "seed-to-soil map:"
+fn parse_header(l: &str) -> Result<(String, String), String> {
+    let names = l.trim().strip_suffix(" map:").and_then(|n| n.split_once("-to-"));
+    let (from, to) = names.ok_or(format!("bad section header: {l}"))?;
+    Ok((from.to_string(), to.to_string()))
+}
+
+fn parse_almanac(s: &str) -> Result<(Vec<i64>, Vec<Layer>), String> {
+    let mut i = s.split("\n\n");
+    let seeds: Vec<i64> = parse_line_of_numbers(i.next().unwrap()).collect();
+    // in pairs they're ranges, whose ends have to fit same as on the map lines
+    if let Some((start, len)) = seeds.iter().tuples().find(|(start, len)| start.checked_add(**len).is_none()) {
+        return Err(format!("seed range {start} {len} doesn't fit in an i64"));
+    }
+    let layers = i
+        .map(|f| {
+            let (from, to) = parse_header(f.lines().next().unwrap())?;
+            Ok(Layer {
+                from,
+                to,
+                lines: parse_section(f)?,
+            })
+        })
+        .collect::<Result<_, String>>()?;
+    Ok((seeds, layers))
 }
 
 fn advance_ranges(mappings: Vec<Vec<MapLine>>, seed_ranges: Vec<Range>) -> i64 {
     mappings
         .into_iter()
-        .fold(seed_ranges, translate_ranges)
+        .fold(seed_ranges, |rs, ms| coalesce(translate_ranges(rs, ms)))
         .iter()
         .map(|x| x.start)
         .min()
         .unwrap()
 }
-fn solve(f: &str) -> i64 {
-    let (s, mappings) = parse(f);
+fn lowest_location((s, mappings): (Vec<i64>, Vec<Vec<MapLine>>)) -> i64 {
     let seed_ranges = s.into_iter().map(|start| Range { start, len: 1 }).collect();
     advance_ranges(mappings, seed_ranges)
 }
-fn solve2(f: &str) -> i64 {
-    let (s, mappings) = parse(f);
+fn lowest_location_ranges((s, mappings): (Vec<i64>, Vec<Vec<MapLine>>)) -> i64 {
     let seed_ranges = s.into_iter().tuples().map(|(start, len)| Range { start, len }).collect();
     advance_ranges(mappings, seed_ranges)
 }
+#[cfg(test)]
+fn solve(f: &str) -> i64 {
+    lowest_location(parse(f))
+}
+#[cfg(test)]
+fn solve2(f: &str) -> i64 {
+    lowest_location_ranges(parse(f))
+}
+
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let (seeds, layers) = r.parse(|| parse_almanac(input).unwrap_or_else(|e| panic!("{e}")));
+    // where lines overlap, the first one listed wins
+    for o in r.step("validate", || overlaps(&layers)) {
+        let l = &layers[o.layer];
+        let label = format!("{}-to-{} overlap", l.from, l.to);
+        r.note(&label, format!("lines {} and {} on {}..{}", o.lines.0 + 1, o.lines.1 + 1, o.start, o.end));
+    }
+    // one layer after the other in the order they're listed, for every query
+    if r.param("layered", false) {
+        let almanac = (seeds, layers.into_iter().map(|l| l.lines).collect_vec());
+        r.part(1, || lowest_location(almanac.clone()));
+        r.part(2, || lowest_location_ranges(almanac.clone()));
+        return;
+    }
+    // the numbers on the first line count as `from`
+    let from = r.param("from", "seed".to_string());
+    let to = r.param("to", "location".to_string());
+    let map = r.step("compose", || translation(&layers, &from, &to).unwrap_or_else(|e| panic!("{e}")));
+    r.part(1, || seeds.iter().map(|&s| map.apply(s)).min().unwrap());
+    r.part(2, || {
+        let ranges = seeds.iter().tuples().map(|(&start, &len)| Range { start, len }).collect();
+        map.apply_ranges(ranges).iter().map(|x| x.start).min().unwrap()
+    });
+    // which `from` ends up at a `to` value, when every one of them has exactly one
+    if r.params.contains_key("location") {
+        let l: i64 = r.param("location", 0);
+        let seed = r.step("inverse", || map.inverse().map(|inv| inv.apply(l)));
+        r.note(
+            &format!("{from} for {to} {l}"),
+            seed.map_or("none or several".to_string(), |s| s.to_string()),
+        );
+    }
+}
 
 fn main() {
-    println!("part 1: {}", solve("inputs/5b"));
-    println!("part 2: {}", solve2("inputs/5b"));
+    runner::main("inputs/5b", run);
 }
 
 //part 2: 75221860 // too low ;-;
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use super::*;
     #[test]
     fn try_range_apply_test() {
         let ml = MapLine {
@@ -193,6 +488,172 @@ mod tests {
         );
     }
     #[test]
+    fn compose_test() {
+        let (seeds, mappings) = parse("inputs/5a");
+        let layer = Piecewise::layer(&mappings[0]);
+        assert_eq!(
+            vec![(i64::MIN, 50, 0), (50, 98, 2), (98, 100, -48), (100, i64::MAX, 0)],
+            layer.0.iter().map(|s| (s.start, s.end, s.offset)).collect_vec()
+        );
+        assert_eq!(vec![81, 14, 57, 13], seeds.iter().map(|&s| layer.apply(s)).collect_vec());
+
+        let map = compose(&mappings);
+        assert_eq!(vec![82, 43, 86, 35], seeds.iter().map(|&s| map.apply(s)).collect_vec());
+        assert!(map.0.iter().tuple_windows().all(|(a, b)| a.end == b.start && a.offset != b.offset));
+        let inverse = map.inverse().unwrap();
+        assert_eq!(seeds, [82, 43, 86, 35].iter().map(|&l| inverse.apply(l)).collect_vec());
+        assert_eq!(Piecewise::identity(), map.then(&inverse));
+        assert_eq!(Piecewise::identity(), Piecewise::layer(&[]));
+        // two values land on 0
+        assert_eq!(None, Piecewise::layer(&[MapLine { dest: 0, src: 5, len: 1 }]).inverse());
+        let mut r = Runner::new();
+        r.params.insert("location".to_string(), "35".to_string());
+        run(&std::fs::read_to_string("inputs/5a").unwrap(), &mut r);
+        assert_eq!(vec![("seed for location 35".to_string(), "13".to_string())], r.notes);
+        let mut r = Runner::new();
+        let huge = format!("seeds: 1\n\nseed-to-location map:\n0 1 {}\n", i64::MAX);
+        assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| run(&huge, &mut r))).is_err());
+        assert_eq!(Some("parse".to_string()), r.running);
+        assert!(parse_mapping(&format!("0 1 {}", i64::MAX)).is_err());
+        assert!(parse_mapping(&format!("{} -5 1", i64::MAX - 1)).is_err());
+        assert_eq!(Err("expected three numbers: 0 1".to_string()), parse_mapping("0 1"));
+        let seeds = format!("seeds: {} 2\n\nseed-to-location map:\n0 1 1\n", i64::MAX - 1);
+        let e = parse_almanac(&seeds).unwrap_err();
+        assert_eq!(format!("seed range {} 2 doesn't fit in an i64", i64::MAX - 1), e);
+
+        for f in ["inputs/5a", "inputs/5b"] {
+            let (seeds, mappings) = parse(f);
+            let map = compose(&mappings);
+            let ranges = seeds.iter().tuples().map(|(&start, &len)| Range { start, len }).collect_vec();
+            let mapped = map.apply_ranges(ranges.clone());
+            assert_eq!(ranges.iter().map(|r| r.len).sum::<i64>(), mapped.iter().map(|r| r.len).sum::<i64>());
+            assert_eq!(
+                advance_ranges(mappings.clone(), ranges.clone()),
+                mapped.iter().map(|r| r.start).min().unwrap()
+            );
+            for r in ranges {
+                for x in [r.start, r.start + r.len / 2, r.start + r.len - 1] {
+                    assert_eq!(advance_ranges(mappings.clone(), vec![Range { start: x, len: 1 }]), map.apply(x));
+                }
+            }
+        }
+    }
+    #[test]
+    fn route_test() {
+        let input = std::fs::read_to_string("inputs/5a").unwrap();
+        let (seeds, layers) = parse_almanac(&input).unwrap();
+        assert_eq!(("seed", "soil"), (layers[0].from.as_str(), layers[0].to.as_str()));
+        assert_eq!(
+            vec![MapLine { dest: 50, src: 98, len: 2 }, MapLine { dest: 52, src: 50, len: 48 }],
+            layers[0].lines
+        );
+        assert_eq!(Ok(vec![1, 2, 3, 4, 5]), route(&layers, "soil", "humidity"));
+        assert_eq!(Ok(vec![]), route(&layers, "water", "water"));
+
+        let soil = translation(&layers, "seed", "soil").unwrap();
+        let humidity = translation(&layers, "soil", "humidity").unwrap();
+        let location = translation(&layers, "humidity", "location").unwrap();
+        for s in seeds {
+            let layered = advance_ranges(parse_str(&input).1, vec![Range { start: s, len: 1 }]);
+            assert_eq!(layered, location.apply(humidity.apply(soil.apply(s))));
+        }
+
+        assert_eq!(
+            Err(RouteError::NoRoute("location".into(), "seed".into())),
+            route(&layers, "location", "seed")
+        );
+        assert_eq!(Err(RouteError::Unknown("dirt".into())), route(&layers, "seed", "dirt"));
+        // light can be had straight from soil, or the long way round
+        let mut shortcut = layers.clone();
+        shortcut.push(Layer {
+            from: "soil".into(),
+            to: "light".into(),
+            lines: vec![],
+        });
+        let e = route(&shortcut, "seed", "location").unwrap_err();
+        assert_eq!("more than one route from seed to location", e.to_string());
+        assert_eq!(Ok(vec![4, 5, 6]), route(&shortcut, "light", "location"));
+        // going round in circles isn't another route
+        let mut circle = layers.clone();
+        circle.push(Layer {
+            from: "water".into(),
+            to: "soil".into(),
+            lines: vec![],
+        });
+        assert_eq!(Ok(vec![0, 1, 2, 3]), route(&circle, "seed", "light"));
+
+        let mut r = Runner::new();
+        r.params.insert("from".to_string(), "soil".to_string());
+        r.params.insert("to".to_string(), "soil".to_string());
+        run(&input, &mut r);
+        assert_eq!(Some("13"), r.answer(1));
+        r = Runner::new();
+        r.params.insert("to".to_string(), "dirt".to_string());
+        assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| run(&input, &mut r))).is_err());
+        assert_eq!(Some("compose".to_string()), r.running);
+    }
+    #[test]
+    fn overlap_test() {
+        let input = "seeds: 1 2\n\nseed-to-soil map:\n10 0 5\n20 3 4\n30 8 1\n40 5 1\n\nsoil-to-location map:\n0 0 9";
+        let (_, layers) = parse_almanac(input).unwrap();
+        assert_eq!(
+            vec![
+                Overlap {
+                    layer: 0,
+                    lines: (0, 1),
+                    start: 3,
+                    end: 5
+                },
+                Overlap {
+                    layer: 0,
+                    lines: (1, 3),
+                    start: 5,
+                    end: 6
+                }
+            ],
+            overlaps(&layers)
+        );
+        // the first line listed wins, with either way of translating
+        assert_eq!(14, compose(&parse_str(input).1).apply(4));
+        assert_eq!(14, advance_ranges(parse_str(input).1, vec![Range { start: 4, len: 1 }]));
+        let mut r = Runner::new();
+        run(input, &mut r);
+        assert_eq!(("seed-to-soil overlap".to_string(), "lines 2 and 4 on 5..6".to_string()), r.notes[1]);
+        assert!(overlaps(&parse_almanac(&std::fs::read_to_string("inputs/5b").unwrap()).unwrap().1).is_empty());
+    }
+    #[test]
+    fn coalesce_test() {
+        let range = |start, len| Range { start, len };
+        assert_eq!(
+            vec![range(0, 10), range(11, 1)],
+            coalesce(vec![range(5, 5), range(11, 1), range(0, 5), range(2, 1), range(7, 0)])
+        );
+        assert!(coalesce(vec![]).is_empty());
+
+        // a big seed range cut into pieces stays as few ranges as the maps allow, layer after layer
+        let (_, mappings) = parse("inputs/5b");
+        let pieces = (0..2_000).map(|i| range(i * 2_000_000, 2_000_000)).collect_vec();
+        let cuts: usize = mappings.iter().map(|ms| 2 * ms.len() + 1).sum();
+        let mut coalesced = pieces.clone();
+        let mut fragmented = pieces.clone();
+        for ms in &mappings {
+            coalesced = coalesce(translate_ranges(coalesced, ms.clone()));
+            fragmented = translate_ranges(fragmented, ms.clone());
+            assert!(coalesced.len() <= cuts, "{}", coalesced.len());
+            assert!(fragmented.len() >= 2_000);
+        }
+        assert_eq!(coalesce(fragmented), coalesced);
+        assert_eq!(
+            advance_ranges(mappings.clone(), pieces),
+            compose(&mappings)
+                .apply_ranges(vec![range(0, 4_000_000_000)])
+                .iter()
+                .map(|r| r.start)
+                .min()
+                .unwrap()
+        );
+    }
+    #[test]
     fn part1() {
         assert_eq!(35, solve("inputs/5a"));
     }
@@ -1,23 +1,27 @@
-use std::fs::read_to_string;
-
+use aoc2023::runner::{self, Runner};
 use itertools::Itertools;
 
 fn parse_line_of_numbers(l: &str) -> impl Iterator<Item = i64> + '_ {
     l.split_whitespace().filter_map(|x| x.parse::<i64>().ok())
 }
+#[cfg(test)]
 fn parse(f: &str) -> impl Iterator<Item = (i64, i64)> + '_ {
-    let (a, b) = read_to_string(f)
-        .unwrap()
+    parse_str(&std::fs::read_to_string(f).unwrap())
+}
+fn parse_str(s: &str) -> impl Iterator<Item = (i64, i64)> {
+    let (a, b) = s
         .lines()
         .map(|x| parse_line_of_numbers(x).collect_vec())
         .collect_tuple()
         .unwrap();
     std::iter::zip(a, b)
 }
+#[cfg(test)]
 fn parse2(f: &str) -> (i64, i64) {
-    read_to_string(f)
-        .unwrap()
-        .lines()
+    parse2_str(&std::fs::read_to_string(f).unwrap())
+}
+fn parse2_str(s: &str) -> (i64, i64) {
+    s.lines()
         .map(|l| l.chars().filter(|c| c.is_numeric()).collect::<String>().parse::<i64>().unwrap())
         .collect_tuple()
         .unwrap()
@@ -46,23 +50,34 @@ fn get_range((time, distance): (i64, i64)) -> (i64, i64) {
     (find_bound(0, time, distance, 1), find_bound(time, time, distance, -1))
 }
 
+fn ways(races: impl Iterator<Item = (i64, i64)>) -> i64 {
+    races.map(get_range).map(|(x, y)| y - x + 1).fold(1, |x, y| x * y)
+}
+
+#[cfg(test)]
 fn solve(f: &str) -> i64 {
-    parse(f).map(get_range).map(|(x, y)| y - x + 1).fold(1, |x, y| x * y)
+    ways(parse(f))
 }
 
+#[cfg(test)]
 fn solve2(f: &str) -> i64 {
-    let (x, y) = get_range(parse2(f));
-    y - x + 1
+    ways(std::iter::once(parse2(f)))
+}
+
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let races = r.parse(|| parse_str(input).collect_vec());
+    let race = r.parse(|| parse2_str(input));
+    r.part(1, || ways(races.into_iter()));
+    r.part(2, || ways(std::iter::once(race)));
 }
 
 fn main() {
-    println!("part 1: {:?}", solve("inputs/6b"));
-    println!("part 2: {:?}", solve2("inputs/6b"));
+    runner::main("inputs/6b", run);
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use super::*;
     #[test]
     fn range_test() {
         assert_eq!((2, 5), get_range((7, 9)));
@@ -1,5 +1,4 @@
-use std::fs::read_to_string;
-
+use aoc2023::runner::{self, Runner};
 use itertools::Itertools;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -50,9 +49,6 @@ fn classify(cards: &Vec<Card>) -> HandType {
         .sorted()
         .rev() // 2 1 1 1
         .collect();
-    if jokers > 0 {
-        println!("-->{}", jokers);
-    }
     match (counts.len(), counts.first().unwrap_or(&0) + jokers) {
         // number of unique cards, highest count
         (_, 5) => HandType::FiveofAKind,
@@ -95,22 +91,33 @@ fn parse_line(l: &str, j: Card) -> (Hand, usize) {
     (parse_hand(f, j), s.parse().unwrap())
 }
 
+#[cfg(test)]
 fn parse(f: &str, j: Card) -> Vec<(Hand, usize)> {
-    read_to_string(f).unwrap().lines().map(|x| parse_line(x, j)).collect()
+    parse_str(&std::fs::read_to_string(f).unwrap(), j)
+}
+
+fn parse_str(s: &str, j: Card) -> Vec<(Hand, usize)> {
+    s.lines().map(|x| parse_line(x, j)).collect()
 }
 
 fn solve(deck: Vec<(Hand, usize)>) -> usize {
     deck.into_iter().sorted().rev().enumerate().map(|(i, (_, score))| (i + 1) * score).sum()
 }
 
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let jacks = r.parse(|| parse_str(input, Card::Jack));
+    let jokers = r.parse(|| parse_str(input, Card::Joker));
+    r.part(1, || solve(jacks));
+    r.part(2, || solve(jokers));
+}
+
 fn main() {
-    println!("part 1: {:?}", solve(parse("inputs/7b", Card::Jack)));
-    println!("part 2: {:?}", solve(parse("inputs/7b", Card::Joker)));
+    runner::main("inputs/7b", run);
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use super::*;
 
     #[test]
     fn hand_parsing_test() {
@@ -1,5 +1,7 @@
-use std::{collections::HashMap, fs::read_to_string};
+use std::collections::HashMap;
 
+use aoc2023::cycle;
+use aoc2023::runner::{self, Runner};
 use itertools::Itertools;
 use num_integer::Integer;
 use substring::Substring;
@@ -81,44 +83,25 @@ fn merge_cycles(x: Cycle, y: Cycle) -> Option<Cycle> {
 
 fn count_period(start: Node, data: Data) -> Vec<Cycle> {
     // returns the start, and period of all found cycles
-    #[derive(Debug)]
-    enum Visited {
-        Once(usize),
-        Twice((usize, usize)),
-    }
-    let mut visited: HashMap<(usize, Node), Visited> = HashMap::new();
-    // (ic, node) -> Visited
     let len = data.directions.len();
-    for (current, node) in walking(start, data).enumerate() {
-        let local = current % len;
-        if ending_node(node) {
-            // every time we roll over the directions,
-            // check if we visited that node before this instruction
-            // i assume that once i'll visit one zs thrice it means all other zs are visited twice
-            let k = (local, node);
-            match visited.get(&k) {
-                None => {
-                    visited.insert(k, Visited::Once(current));
-                }
-                Some(Visited::Once(previous)) => {
-                    visited.insert(k, Visited::Twice((*previous, current)));
-                }
-                Some(Visited::Twice(_)) => break,
-            }
+    // (ic, node), the node reached after taking ic steps (mod len)
+    let step = |&(ic, node): &(usize, Node)| ((ic + 1) % len, follow(data.directions[ic], data.nodes[&node]));
+    let found = cycle::brent((0, start), step);
+    // only the zs inside the loop come back, starts are counted from the first step
+    let mut state = cycle::advance((0, start), step, found.start);
+    let mut cycles = vec![];
+    for k in found.start..found.start + found.period {
+        if k > 0 && ending_node(state.1) {
+            cycles.push(Cycle {
+                start: (k - 1) as isize,
+                period: found.period as isize,
+            });
         }
+        state = step(&state);
     }
-
-    visited
-        .values()
-        .map(|x| match x {
-            Visited::Twice((a, b)) => Cycle {
-                start: *a as isize,
-                period: (b - a) as isize,
-            },
-            x => panic!("unexpected {:?}", x),
-        })
-        .collect()
+    cycles
 }
+
 fn part2(data: Data) -> isize {
     let keys: Vec<_> = data.nodes.keys().copied().filter(start_node).collect();
     keys.into_iter()
@@ -135,8 +118,12 @@ fn part2(data: Data) -> isize {
         + 1
 }
 
+#[cfg(test)]
 fn parse(f: &str) -> Data {
-    let file_content = read_to_string(f).unwrap();
+    parse_str(&std::fs::read_to_string(f).unwrap())
+}
+
+fn parse_str(file_content: &str) -> Data {
     let (dirs, nodes) = file_content.split("\n\n").collect_tuple().unwrap();
 
     let directions = dirs.chars().map(parse_dir).collect();
@@ -150,14 +137,19 @@ fn part1(data: Data) -> usize {
     count_steps(data, start, end)
 }
 
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let data = r.parse(|| parse_str(input));
+    r.part(1, || part1(data.clone()));
+    r.part(2, || part2(data));
+}
+
 fn main() {
-    println!("part 1: {:?}", part1(parse("inputs/8b")));
-    println!("part 2: {:?}", part2(parse("inputs/8b")));
+    runner::main("inputs/8b", run);
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use super::*;
     #[test]
     fn parse_line_test() {
         let a = (encode_node("AAA"), (encode_node("BBB"), encode_node("CCC")));
@@ -1,5 +1,4 @@
-use std::fs::read_to_string;
-
+use aoc2023::runner::{self, Runner};
 use itertools::Itertools;
 use std::iter;
 fn parse_line(l: &str) -> Vec<i64> {
@@ -35,18 +34,29 @@ fn backwards(x: Vec<i64>) -> i64 {
         .unwrap()
 }
 
+fn extrapolate(histories: &[Vec<i64>], direction: fn(Vec<i64>) -> i64) -> i64 {
+    histories.iter().cloned().map(direction).sum()
+}
+
+#[cfg(test)]
 fn solve(f: &str, direction: fn(Vec<i64>) -> i64) -> i64 {
-    read_to_string(f).unwrap().lines().map(parse_line).map(direction).sum()
+    let histories = std::fs::read_to_string(f).unwrap().lines().map(parse_line).collect::<Vec<_>>();
+    extrapolate(&histories, direction)
+}
+
+pub(crate) fn run(input: &str, r: &mut Runner) {
+    let histories = r.parse(|| input.lines().map(parse_line).collect::<Vec<_>>());
+    r.part(1, || extrapolate(&histories, forwards));
+    r.part(2, || extrapolate(&histories, backwards));
 }
 
 fn main() {
-    println!("part 1: {:?}", solve("inputs/9b", forwards));
-    println!("part 1: {:?}", solve("inputs/9a", backwards));
+    runner::main("inputs/9b", run);
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::*;
+    use super::*;
     #[test]
     fn diff_test() {
         assert_eq!(vec![0, 2, 4, 6, 8], diff(vec![3, 3, 5, 9, 15, 23]));
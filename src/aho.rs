@@ -0,0 +1,169 @@
+use std::collections::VecDeque;
+
+const NONE: u32 = u32::MAX;
+
+// multi pattern matcher, every pattern gets found in one pass over the text,
+// overlapping matches included
+pub struct Automaton<V> {
+    // dense transitions, fallbacks already folded in
+    delta: Vec<[u32; 256]>,
+    // patterns ending in each state, longest first
+    out: Vec<Vec<usize>>,
+    values: Vec<V>,
+    lens: Vec<usize>,
+}
+
+// byte offsets into the text, `pattern` is the position in the table
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Match {
+    pub pattern: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl<V> Automaton<V> {
+    pub fn new<'p>(patterns: impl IntoIterator<Item = (V, &'p str)>) -> Self {
+        Self::build(patterns, false)
+    }
+
+    // folds ascii letters only, anything else still has to match exactly
+    pub fn ignore_ascii_case<'p>(patterns: impl IntoIterator<Item = (V, &'p str)>) -> Self {
+        Self::build(patterns, true)
+    }
+
+    fn build<'p>(patterns: impl IntoIterator<Item = (V, &'p str)>, fold: bool) -> Self {
+        let mut delta = vec![[NONE; 256]];
+        let mut out = vec![vec![]];
+        let mut values = vec![];
+        let mut lens = vec![];
+        for (i, (v, p)) in patterns.into_iter().enumerate() {
+            assert!(!p.is_empty(), "empty pattern");
+            let mut s = 0;
+            for b in p.bytes() {
+                let b = if fold { b.to_ascii_lowercase() } else { b };
+                if delta[s][b as usize] == NONE {
+                    delta[s][b as usize] = delta.len() as u32;
+                    delta.push([NONE; 256]);
+                    out.push(vec![]);
+                }
+                s = delta[s][b as usize] as usize;
+            }
+            out[s].push(i);
+            values.push(v);
+            lens.push(p.len());
+        }
+
+        // breadth first, so the fallback of a state is complete before the state itself
+        let mut fail = vec![0; delta.len()];
+        let mut queue = VecDeque::new();
+        for t in delta[0].iter_mut() {
+            match *t {
+                NONE => *t = 0,
+                _ => queue.push_back(*t as usize),
+            }
+        }
+        while let Some(s) = queue.pop_front() {
+            let f = fail[s];
+            let inherited = out[f].clone();
+            out[s].extend(inherited);
+            let fallback = delta[f];
+            for (t, &ft) in delta[s].iter_mut().zip(&fallback) {
+                match *t {
+                    NONE => *t = ft,
+                    _ => {
+                        fail[*t as usize] = ft as usize;
+                        queue.push_back(*t as usize);
+                    }
+                }
+            }
+        }
+        // only lowercase made it into the trie, so uppercase just goes the same way
+        if fold {
+            for row in &mut delta {
+                for b in b'A'..=b'Z' {
+                    row[b as usize] = row[b.to_ascii_lowercase() as usize];
+                }
+            }
+        }
+        Automaton { delta, out, values, lens }
+    }
+
+    pub fn value(&self, pattern: usize) -> &V {
+        &self.values[pattern]
+    }
+
+    // ordered by where they end
+    pub fn find_iter<'a>(&'a self, text: &'a str) -> impl Iterator<Item = Match> + 'a {
+        let mut s = 0;
+        text.bytes().enumerate().flat_map(move |(i, b)| {
+            s = self.delta[s][b as usize] as usize;
+            self.out[s].iter().map(move |&pattern| Match {
+                pattern,
+                start: i + 1 - self.lens[pattern],
+                end: i + 1,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    fn naive(patterns: &[&str], text: &str) -> Vec<Match> {
+        let mut found = vec![];
+        for end in 1..=text.len() {
+            let mut here = vec![];
+            for (pattern, p) in patterns.iter().enumerate() {
+                if text[..end].ends_with(p) {
+                    here.push(Match {
+                        pattern,
+                        start: end - p.len(),
+                        end,
+                    });
+                }
+            }
+            here.sort_by_key(|m| m.start);
+            found.extend(here);
+        }
+        found
+    }
+
+    #[test]
+    fn overlapping_test() {
+        let ac = Automaton::new([(8, "eight"), (2, "two"), (1, "one")]);
+        let found: Vec<_> = ac.find_iter("eightwone").map(|m| (*ac.value(m.pattern), m.start, m.end)).collect();
+        assert_eq!(vec![(8, 0, 5), (2, 4, 7), (1, 6, 9)], found);
+    }
+
+    #[test]
+    fn suffix_test() {
+        let patterns = ["he", "she", "his", "hers"];
+        let ac = Automaton::new(patterns.iter().map(|p| ((), *p)));
+        let found: Vec<_> = ac.find_iter("ushers").map(|m| patterns[m.pattern]).collect();
+        assert_eq!(vec!["she", "he", "hers"], found);
+        assert_eq!(0, ac.find_iter("").count());
+    }
+
+    #[test]
+    fn ignore_case_test() {
+        let ac = Automaton::ignore_ascii_case([((), "One"), ((), "zwölf")]);
+        let found: Vec<_> = ac.find_iter("oNEzwÖlfZWölf").map(|m| (m.start, m.end)).collect();
+        assert_eq!(vec![(0, 3), (9, 15)], found);
+        assert_eq!(0, Automaton::new([((), "one")]).find_iter("ONE").count());
+    }
+
+    #[test]
+    fn naive_test() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let mut word = |n| (0..rng.gen_range(1..=n)).map(|_| rng.gen_range(b'a'..=b'c') as char).collect::<String>();
+            let patterns: Vec<String> = (0..5).map(|_| word(4)).collect();
+            let text = word(30);
+            let patterns: Vec<&str> = patterns.iter().map(String::as_str).collect();
+            let ac = Automaton::new(patterns.iter().map(|p| ((), *p)));
+            assert_eq!(naive(&patterns, &text), ac.find_iter(&text).collect::<Vec<_>>(), "{patterns:?} in {text}");
+        }
+    }
+}
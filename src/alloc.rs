@@ -0,0 +1,163 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+// counters are per thread, so concurrent solves don't see each other's
+// allocations, but neither do they see those of threads they spawn.
+// live can go negative when a thread frees what another one allocated
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    static BYTES: Cell<usize> = const { Cell::new(0) };
+    static LIVE: Cell<isize> = const { Cell::new(0) };
+    static PEAK: Cell<isize> = const { Cell::new(0) };
+}
+
+pub struct Counting;
+
+// try_with, as the allocator still gets called while thread locals are torn down
+fn grow(size: usize) {
+    let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+    let _ = BYTES.try_with(|b| b.set(b.get() + size));
+    if let Ok(live) = LIVE.try_with(|l| {
+        l.set(l.get() + size as isize);
+        l.get()
+    }) {
+        let _ = PEAK.try_with(|p| p.set(p.get().max(live)));
+    }
+}
+
+fn shrink(size: usize) {
+    let _ = LIVE.try_with(|l| l.set(l.get() - size as isize));
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let p = System.alloc(layout);
+        if !p.is_null() {
+            grow(layout.size());
+        }
+        p
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let p = System.alloc_zeroed(layout);
+        if !p.is_null() {
+            grow(layout.size());
+        }
+        p
+    }
+
+    unsafe fn dealloc(&self, p: *mut u8, layout: Layout) {
+        System.dealloc(p, layout);
+        shrink(layout.size());
+    }
+
+    unsafe fn realloc(&self, p: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let q = System.realloc(p, layout, new_size);
+        if !q.is_null() {
+            // counted as a fresh allocation of the new size, the old block is gone
+            shrink(layout.size());
+            grow(new_size);
+        }
+        q
+    }
+}
+
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    pub allocations: usize,
+    pub bytes: usize,
+    // highest live memory above what was live when the measurement started
+    pub peak: usize,
+}
+
+pub fn enabled() -> bool {
+    cfg!(feature = "alloc-stats")
+}
+
+pub fn measure<R>(f: impl FnOnce() -> R) -> (R, Option<AllocStats>) {
+    if !enabled() {
+        return (f(), None);
+    }
+    let live = LIVE.get();
+    // only this thread's peak is reset, so nested measurements still clobber the outer one
+    let outer = PEAK.replace(live);
+    let allocations = ALLOCATIONS.get();
+    let bytes = BYTES.get();
+    let r = f();
+    let peak = PEAK.get();
+    PEAK.set(peak.max(outer));
+    let stats = AllocStats {
+        allocations: ALLOCATIONS.get() - allocations,
+        bytes: BYTES.get() - bytes,
+        peak: (peak - live).max(0) as usize,
+    };
+    (r, Some(stats))
+}
+
+pub fn human_bytes(b: usize) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let mut v = b as f64;
+    let mut u = 0;
+    while v >= 1024.0 && u < units.len() - 1 {
+        v /= 1024.0;
+        u += 1;
+    }
+    if u == 0 {
+        format!("{b}B")
+    } else {
+        format!("{v:.1}{}", units[u])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn human_bytes_test() {
+        assert_eq!("12B", human_bytes(12));
+        assert_eq!("1.5KiB", human_bytes(1536));
+        assert_eq!("3.0MiB", human_bytes(3 * 1024 * 1024));
+    }
+
+    #[test]
+    #[cfg(not(feature = "alloc-stats"))]
+    fn disabled_test() {
+        assert_eq!((5, None), measure(|| 5));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc-stats")]
+    fn counting_test() {
+        let (v, stats) = measure(|| {
+            let a: Vec<u64> = Vec::with_capacity(1000);
+            let b: Vec<u64> = Vec::with_capacity(1000);
+            drop(a);
+            drop(b);
+            let c: Vec<u8> = Vec::with_capacity(100);
+            c.capacity()
+        });
+        let stats = stats.unwrap();
+        assert_eq!(100, v);
+        assert_eq!(3, stats.allocations);
+        assert_eq!(16100, stats.bytes);
+        assert_eq!(16000, stats.peak);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc-stats")]
+    fn threads_test() {
+        // the other thread's allocations don't show up here
+        let (inner, outer) = measure(|| {
+            let t = std::thread::spawn(|| measure(|| Vec::<u8>::with_capacity(1 << 20)).1.unwrap());
+            let _v: Vec<u8> = Vec::with_capacity(10);
+            t.join().unwrap()
+        });
+        assert_eq!(1 << 20, inner.peak);
+        assert!(outer.unwrap().peak < 1 << 20);
+    }
+}
@@ -0,0 +1,266 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::{Add, Mul, Sub};
+
+// integer widths a coordinate can be made of, narrower ones are there so
+// grids can be packed tighter once we know how big the inputs get
+pub trait Int: Copy + Ord + Hash + Debug + Default + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> {
+    const ZERO: Self;
+    const ONE: Self;
+    fn checked_add(self, o: Self) -> Option<Self>;
+    fn checked_sub(self, o: Self) -> Option<Self>;
+    fn abs_diff(self, o: Self) -> usize;
+    fn to_i128(self) -> i128;
+    fn from_i128(v: i128) -> Option<Self>;
+}
+
+macro_rules! int {
+    ($($t:ty),*) => {$(
+        impl Int for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            fn checked_add(self, o: Self) -> Option<Self> {
+                <$t>::checked_add(self, o)
+            }
+            fn checked_sub(self, o: Self) -> Option<Self> {
+                <$t>::checked_sub(self, o)
+            }
+            fn abs_diff(self, o: Self) -> usize {
+                <$t>::abs_diff(self, o) as usize
+            }
+            fn to_i128(self) -> i128 {
+                self as i128
+            }
+            fn from_i128(v: i128) -> Option<Self> {
+                v.try_into().ok()
+            }
+        }
+    )*};
+}
+
+int!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dir {
+    Left,
+    Right,
+    Down,
+    Up,
+}
+
+pub static DIRECTIONS: &[Dir] = &[Dir::Left, Dir::Right, Dir::Down, Dir::Up];
+
+// y grows downwards, same as line numbers in the input
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T: Int> Point2<T> {
+    pub fn new(x: T, y: T) -> Self {
+        Point2 { x, y }
+    }
+
+    pub fn manhattan(self, o: Self) -> usize {
+        self.x.abs_diff(o.x) + self.y.abs_diff(o.y)
+    }
+
+    // None when the step would leave the integer type
+    pub fn step(self, d: Dir) -> Option<Self> {
+        let Point2 { x, y } = self;
+        Some(match d {
+            Dir::Left => Point2::new(x.checked_sub(T::ONE)?, y),
+            Dir::Right => Point2::new(x.checked_add(T::ONE)?, y),
+            Dir::Down => Point2::new(x, y.checked_add(T::ONE)?),
+            Dir::Up => Point2::new(x, y.checked_sub(T::ONE)?),
+        })
+    }
+
+    // same as `step`, but has to stay inside of `0..size` on both axes
+    pub fn step_within(self, d: Dir, size: Self) -> Option<Self> {
+        self.step(d).filter(|p| p.inside(size))
+    }
+
+    pub fn inside(self, size: Self) -> bool {
+        T::ZERO <= self.x && self.x < size.x && T::ZERO <= self.y && self.y < size.y
+    }
+
+    pub fn neighbours(self) -> impl Iterator<Item = Self> {
+        DIRECTIONS.iter().filter_map(move |&d| self.step(d))
+    }
+
+    pub fn neighbours8(self) -> impl Iterator<Item = Self> {
+        let offsets = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+        offsets.into_iter().filter_map(move |(dx, dy)| self.offset(dx, dy))
+    }
+
+    pub fn offset(self, dx: i128, dy: i128) -> Option<Self> {
+        Some(Point2::new(T::from_i128(self.x.to_i128() + dx)?, T::from_i128(self.y.to_i128() + dy)?))
+    }
+
+    pub fn cast<U: Int>(self) -> Option<Point2<U>> {
+        Some(Point2::new(U::from_i128(self.x.to_i128())?, U::from_i128(self.y.to_i128())?))
+    }
+}
+
+impl<T: Int> Point3<T> {
+    pub fn new(x: T, y: T, z: T) -> Self {
+        Point3 { x, y, z }
+    }
+
+    pub fn manhattan(self, o: Self) -> usize {
+        self.x.abs_diff(o.x) + self.y.abs_diff(o.y) + self.z.abs_diff(o.z)
+    }
+
+    pub fn neighbours(self) -> impl Iterator<Item = Self> {
+        let offsets = [(-1, 0, 0), (1, 0, 0), (0, -1, 0), (0, 1, 0), (0, 0, -1), (0, 0, 1)];
+        offsets.into_iter().filter_map(move |(dx, dy, dz)| self.offset(dx, dy, dz))
+    }
+
+    pub fn offset(self, dx: i128, dy: i128, dz: i128) -> Option<Self> {
+        Some(Point3::new(
+            T::from_i128(self.x.to_i128() + dx)?,
+            T::from_i128(self.y.to_i128() + dy)?,
+            T::from_i128(self.z.to_i128() + dz)?,
+        ))
+    }
+
+    pub fn cast<U: Int>(self) -> Option<Point3<U>> {
+        Some(Point3::new(
+            U::from_i128(self.x.to_i128())?,
+            U::from_i128(self.y.to_i128())?,
+            U::from_i128(self.z.to_i128())?,
+        ))
+    }
+}
+
+impl<T: Int> Add for Point2<T> {
+    type Output = Self;
+    fn add(self, o: Self) -> Self {
+        Point2::new(self.x + o.x, self.y + o.y)
+    }
+}
+
+impl<T: Int> Sub for Point2<T> {
+    type Output = Self;
+    fn sub(self, o: Self) -> Self {
+        Point2::new(self.x - o.x, self.y - o.y)
+    }
+}
+
+impl<T: Int> Mul<T> for Point2<T> {
+    type Output = Self;
+    fn mul(self, k: T) -> Self {
+        Point2::new(self.x * k, self.y * k)
+    }
+}
+
+impl<T: Int> Add for Point3<T> {
+    type Output = Self;
+    fn add(self, o: Self) -> Self {
+        Point3::new(self.x + o.x, self.y + o.y, self.z + o.z)
+    }
+}
+
+impl<T: Int> Sub for Point3<T> {
+    type Output = Self;
+    fn sub(self, o: Self) -> Self {
+        Point3::new(self.x - o.x, self.y - o.y, self.z - o.z)
+    }
+}
+
+impl<T: Int> Mul<T> for Point3<T> {
+    type Output = Self;
+    fn mul(self, k: T) -> Self {
+        Point3::new(self.x * k, self.y * k, self.z * k)
+    }
+}
+
+impl<T> From<(T, T)> for Point2<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Point2 { x, y }
+    }
+}
+
+impl<T> From<Point2<T>> for (T, T) {
+    fn from(p: Point2<T>) -> Self {
+        (p.x, p.y)
+    }
+}
+
+impl<T> From<(T, T, T)> for Point3<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Point3 { x, y, z }
+    }
+}
+
+impl<T> From<Point3<T>> for (T, T, T) {
+    fn from(p: Point3<T>) -> Self {
+        (p.x, p.y, p.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem::size_of;
+
+    #[test]
+    fn arithmetic_test() {
+        let a = Point2::new(3i32, -2);
+        let b = Point2::new(-1, 5);
+        assert_eq!(Point2::new(2, 3), a + b);
+        assert_eq!(Point2::new(4, -7), a - b);
+        assert_eq!(Point2::new(6, -4), a * 2);
+        assert_eq!(11, a.manhattan(b));
+        let c = Point3::new(1u8, 2, 3);
+        assert_eq!(Point3::new(2, 4, 6), c + c);
+        assert_eq!(6, c.manhattan(Point3::default()));
+        assert_eq!(6, Point3::default().manhattan(c));
+    }
+
+    #[test]
+    fn step_test() {
+        let p = Point2::new(0u16, 0);
+        assert_eq!(None, p.step(Dir::Left));
+        assert_eq!(None, p.step(Dir::Up));
+        assert_eq!(Some(Point2::new(1, 0)), p.step(Dir::Right));
+        assert_eq!(Some(Point2::new(0, 1)), p.step(Dir::Down));
+        assert_eq!(Some(Point2::new(-1, 0)), Point2::new(0i8, 0).step(Dir::Left));
+        assert_eq!(None, Point2::new(127i8, 0).step(Dir::Right));
+
+        let size = Point2::new(2u32, 2);
+        assert_eq!(None, Point2::new(1, 1).step_within(Dir::Right, size));
+        assert_eq!(Some(Point2::new(0, 1)), Point2::new(1, 1).step_within(Dir::Left, size));
+    }
+
+    #[test]
+    fn neighbours_test() {
+        assert_eq!(2, Point2::new(0usize, 0).neighbours().count());
+        assert_eq!(4, Point2::new(1usize, 1).neighbours().count());
+        assert_eq!(3, Point2::new(0u8, 0).neighbours8().count());
+        assert_eq!(8, Point2::new(0i64, 0).neighbours8().count());
+        assert_eq!(6, Point3::new(0i32, 0, 0).neighbours().count());
+        assert_eq!(3, Point3::new(0u32, 0, 0).neighbours().count());
+    }
+
+    #[test]
+    fn width_test() {
+        assert_eq!(2, size_of::<Point2<u8>>());
+        assert_eq!(4, size_of::<Point2<u16>>());
+        assert_eq!(8, size_of::<Point2<u32>>());
+        assert_eq!(12, size_of::<Point3<i32>>());
+        assert_eq!(Some(Point2::new(300u16, 2)), Point2::new(300u32, 2).cast());
+        assert_eq!(None, Point2::new(300u32, 2).cast::<u8>());
+        assert_eq!(None, Point2::new(-1i32, 2).cast::<usize>());
+        assert_eq!((1, 2), Point2::from((1, 2)).into());
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+// state at step `start` is the first one that repeats, and it comes back every `period` steps
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    // smallest step with the same state as step `n`
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.period
+        }
+    }
+}
+
+fn hash<S: Hash>(s: &S) -> u64 {
+    let mut h = DefaultHasher::new();
+    s.hash(&mut h);
+    h.finish()
+}
+
+// states are compared by hash first, the full comparison only guards against collisions
+struct Hashed<S> {
+    state: S,
+    hash: u64,
+}
+
+impl<S: Hash + Eq> Hashed<S> {
+    fn new(state: S) -> Self {
+        let hash = hash(&state);
+        Hashed { state, hash }
+    }
+
+    fn advance(&mut self, step: &mut impl FnMut(&S) -> S) {
+        *self = Hashed::new(step(&self.state));
+    }
+
+    fn same(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.state == other.state
+    }
+}
+
+impl<S: Clone> Clone for Hashed<S> {
+    fn clone(&self) -> Self {
+        Hashed {
+            state: self.state.clone(),
+            hash: self.hash,
+        }
+    }
+}
+
+fn find_start<S: Clone + Hash + Eq>(init: &S, period: usize, step: &mut impl FnMut(&S) -> S) -> usize {
+    let mut tortoise = Hashed::new(init.clone());
+    let mut hare = tortoise.clone();
+    for _ in 0..period {
+        hare.advance(step);
+    }
+    let mut start = 0;
+    while !tortoise.same(&hare) {
+        tortoise.advance(step);
+        hare.advance(step);
+        start += 1;
+    }
+    start
+}
+
+// never returns if the states don't repeat, finite state spaces always do
+pub fn brent<S: Clone + Hash + Eq>(init: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = Hashed::new(init.clone());
+    let mut hare = Hashed::new(step(&init));
+    while !tortoise.same(&hare) {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare.advance(&mut step);
+        period += 1;
+    }
+    let start = find_start(&init, period, &mut step);
+    Cycle { start, period }
+}
+
+pub fn floyd<S: Clone + Hash + Eq>(init: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    let mut tortoise = Hashed::new(step(&init));
+    let mut hare = Hashed::new(step(&tortoise.state));
+    while !tortoise.same(&hare) {
+        tortoise.advance(&mut step);
+        hare.advance(&mut step);
+        hare.advance(&mut step);
+    }
+    let mut period = 1;
+    hare = Hashed::new(step(&tortoise.state));
+    while !tortoise.same(&hare) {
+        hare.advance(&mut step);
+        period += 1;
+    }
+    let start = find_start(&init, period, &mut step);
+    Cycle { start, period }
+}
+
+// one step per state and never going back, in exchange for holding on to every state up to
+// the first repeat, for when a step costs more than keeping a state around
+pub fn record<S: Hash + Eq>(init: S, mut step: impl FnMut(&S) -> S) -> (Cycle, Vec<S>) {
+    let mut states: Vec<S> = vec![];
+    let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut s = Hashed::new(init);
+    loop {
+        let indices = seen.entry(s.hash).or_default();
+        if let Some(&start) = indices.iter().find(|&&i| states[i] == s.state) {
+            let period = states.len() - start;
+            return (Cycle { start, period }, states);
+        }
+        indices.push(states.len());
+        let next = Hashed::new(step(&s.state));
+        states.push(std::mem::replace(&mut s, next).state);
+    }
+}
+
+pub fn advance<S>(init: S, mut step: impl FnMut(&S) -> S, n: usize) -> S {
+    let mut s = init;
+    for _ in 0..n {
+        s = step(&s);
+    }
+    s
+}
+
+// state after `n` steps, without actually doing all of them
+pub fn jump<S: Hash + Eq>(init: S, step: impl FnMut(&S) -> S, n: usize) -> S {
+    let (cycle, mut states) = record(init, step);
+    states.swap_remove(cycle.reduce(n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // rho shaped: 0 -> 1 -> 2 -> 3 -> 4 -> 5 -> 6 -> 3
+    fn rho(x: &usize) -> usize {
+        if *x == 6 {
+            3
+        } else {
+            x + 1
+        }
+    }
+
+    #[test]
+    fn brent_test() {
+        assert_eq!(Cycle { start: 3, period: 4 }, brent(0, rho));
+        assert_eq!(Cycle { start: 0, period: 4 }, brent(5, rho));
+        assert_eq!(Cycle { start: 0, period: 1 }, brent(7, |x| *x));
+        assert_eq!(Cycle { start: 0, period: 7 }, brent(0, |x| (x + 1) % 7));
+    }
+
+    #[test]
+    fn floyd_test() {
+        assert_eq!(Cycle { start: 3, period: 4 }, floyd(0, rho));
+        assert_eq!(Cycle { start: 0, period: 4 }, floyd(5, rho));
+        assert_eq!(Cycle { start: 0, period: 1 }, floyd(7, |x| *x));
+        assert_eq!(Cycle { start: 0, period: 7 }, floyd(0, |x| (x + 1) % 7));
+    }
+
+    #[test]
+    fn agree_test() {
+        // x^2 + 1 mod m, classic pollard rho sequence
+        for m in 2..200 {
+            let f = |x: &u64| (x * x + 1) % m;
+            assert_eq!(brent(2, f), floyd(2, f));
+        }
+    }
+
+    #[test]
+    fn record_test() {
+        let mut steps = 0;
+        let (c, states) = record(0, |x| {
+            steps += 1;
+            rho(x)
+        });
+        assert_eq!(Cycle { start: 3, period: 4 }, c);
+        assert_eq!(vec![0, 1, 2, 3, 4, 5, 6], states);
+        assert_eq!(7, steps);
+        for m in 2..200 {
+            let f = |x: &u64| (x * x + 1) % m;
+            assert_eq!(brent(2, f), record(2, f).0);
+        }
+    }
+
+    #[test]
+    fn jump_test() {
+        let c = brent(0, rho);
+        assert_eq!(2, c.reduce(2));
+        assert_eq!(3, c.reduce(7));
+        assert_eq!(5, c.reduce(1_000_000_001));
+        for n in 0..50 {
+            assert_eq!(advance(0, rho, n), jump(0, rho, n));
+        }
+        assert_eq!(5, jump(0, rho, 1_000_000_001));
+    }
+
+    #[test]
+    fn structured_state_test() {
+        let step = |v: &Vec<u8>| {
+            let mut v = v.clone();
+            v.rotate_left(1);
+            v
+        };
+        assert_eq!(Cycle { start: 0, period: 3 }, brent(vec![1, 2, 3], step));
+        assert_eq!(Cycle { start: 0, period: 1 }, brent(vec![1, 1, 1], step));
+    }
+}
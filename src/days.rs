@@ -0,0 +1,127 @@
+use crate::runner::Runner;
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+pub struct Day {
+    pub number: usize,
+    // the input the day's own binary runs on
+    pub input: &'static str,
+    pub run: fn(&str, &mut Runner),
+}
+
+// the days are binaries of their own, they get pulled in here as well so
+// they can be driven in-process; lints are already reported for the binaries
+macro_rules! days {
+    ($($number:literal: $module:ident = $path:literal, $input:literal;)*) => {
+        $(
+            #[path = $path]
+            #[allow(warnings, clippy::all)]
+            mod $module;
+        )*
+
+        pub static DAYS: &[Day] = &[$(Day {
+            number: $number,
+            input: $input,
+            run: $module::run,
+        },)*];
+    };
+}
+
+days! {
+    1: day1 = "1.rs", "inputs/1b";
+    2: day2 = "2.rs", "inputs/2b";
+    3: day3 = "3.rs", "inputs/3b";
+    4: day4 = "4.rs", "inputs/4b";
+    5: day5 = "5.rs", "inputs/5b";
+    6: day6 = "6.rs", "inputs/6b";
+    7: day7 = "7.rs", "inputs/7b";
+    8: day8 = "8.rs", "inputs/8b";
+    9: day9 = "9.rs", "inputs/9b";
+    10: day10 = "10.rs", "inputs/10b";
+    11: day11 = "11.rs", "inputs/11b";
+    12: day12 = "12.rs", "inputs/12b";
+    13: day13 = "13.rs", "inputs/13b";
+    14: day14 = "14.rs", "inputs/14b";
+    15: day15 = "15.rs", "inputs/15b";
+    16: day16 = "16.rs", "inputs/16b";
+    17: day17 = "17.rs", "inputs/17b";
+    18: day18 = "18.rs", "inputs/18b";
+    19: day19 = "19.rs", "inputs/19b";
+    20: day20 = "20.rs", "inputs/20b";
+    21: day21 = "21.rs", "inputs/21b";
+    22: day22 = "22.rs", "inputs/22a";
+    23: day23 = "23.rs", "inputs/23b";
+    24: day24 = "24.rs", "inputs/24b";
+}
+
+pub fn get(number: usize) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.number == number)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    // days report bad input by panicking, `step` is where it happened
+    Panicked { step: Option<String>, message: String },
+    NoSuchPart(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Panicked { step: Some(s), message } if s == "parse" => write!(f, "parse error: {message}"),
+            Error::Panicked { step: Some(s), message } => write!(f, "error in {s}: {message}"),
+            Error::Panicked { step: None, message } => write!(f, "error: {message}"),
+            Error::NoSuchPart(n) => write!(f, "no part {n}"),
+        }
+    }
+}
+
+fn panic_message(e: Box<dyn Any + Send>) -> String {
+    match e.downcast::<String>() {
+        Ok(s) => *s,
+        Err(e) => e.downcast_ref::<&str>().map_or("unknown panic", |s| s).to_string(),
+    }
+}
+
+impl Day {
+    // the runner keeps whatever got done before an error
+    pub fn try_run(&self, input: &str, r: &mut Runner) -> Result<(), Error> {
+        panic::catch_unwind(AssertUnwindSafe(|| (self.run)(input, r))).map_err(|e| Error::Panicked {
+            step: r.running.take(),
+            message: panic_message(e),
+        })
+    }
+
+    // runs only the one part
+    pub fn solve(&self, part: usize, input: &str, params: HashMap<String, String>) -> (Runner, Result<String, Error>) {
+        let mut r = Runner::new();
+        r.only = Some(part);
+        r.params = params;
+        let answer = self
+            .try_run(input, &mut r)
+            .and_then(|_| r.answer(part).map(str::to_string).ok_or(Error::NoSuchPart(part)));
+        (r, answer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solve_test() {
+        let day = get(6).unwrap();
+        let (r, answer) = day.solve(1, "Time: 7 15 30\nDistance: 9 40 200", HashMap::new());
+        assert_eq!(Ok("288".to_string()), answer);
+        assert_eq!(
+            vec!["parse", "parse", "part 1"],
+            r.steps.iter().map(|s| s.label.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(Err(Error::NoSuchPart(3)), day.solve(3, "Time: 7\nDistance: 9", HashMap::new()).1);
+        let (r, answer) = day.solve(1, "garbage", HashMap::new());
+        assert!(r.steps.is_empty());
+        assert!(answer.unwrap_err().to_string().starts_with("parse error: "));
+    }
+}
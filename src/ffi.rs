@@ -0,0 +1,96 @@
+use crate::days;
+use std::collections::HashMap;
+use std::ffi::{c_char, CString};
+use std::ptr;
+use std::slice;
+
+/// Outcome of `aoc_solve`, exactly one of answer and error is set.
+pub struct AocResult {
+    answer: Option<CString>,
+    error: Option<CString>,
+}
+
+fn c_string(s: String) -> CString {
+    // panic messages can quote the input, which might hold anything
+    CString::new(s.replace('\0', "\\0")).unwrap()
+}
+
+fn solve(day: u32, part: u32, input: &[u8]) -> Result<String, String> {
+    let input = std::str::from_utf8(input).map_err(|e| format!("input is not utf-8: {e}"))?;
+    let d = days::get(day as usize).ok_or(format!("no such day: {day}"))?;
+    d.solve(part as usize, input, HashMap::new()).1.map_err(|e| e.to_string())
+}
+
+/// Solves one part of a day for the `len` bytes of input at `input`.
+/// Never returns NULL, release the result with `aoc_free`.
+///
+/// # Safety
+///
+/// `input` must point to `len` readable bytes, it may be NULL when `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn aoc_solve(day: u32, part: u32, input: *const u8, len: usize) -> *mut AocResult {
+    let input = if len == 0 { &[][..] } else { slice::from_raw_parts(input, len) };
+    let (answer, error) = match solve(day, part, input) {
+        Ok(a) => (Some(c_string(a)), None),
+        Err(e) => (None, Some(c_string(e))),
+    };
+    Box::into_raw(Box::new(AocResult { answer, error }))
+}
+
+/// The answer, or NULL if solving failed. Owned by the result.
+///
+/// # Safety
+///
+/// `result` must come from `aoc_solve` and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn aoc_answer(result: *const AocResult) -> *const c_char {
+    (*result).answer.as_ref().map_or(ptr::null(), |s| s.as_ptr())
+}
+
+/// Why solving failed, or NULL if it didn't. Owned by the result.
+///
+/// # Safety
+///
+/// `result` must come from `aoc_solve` and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn aoc_error(result: *const AocResult) -> *const c_char {
+    (*result).error.as_ref().map_or(ptr::null(), |s| s.as_ptr())
+}
+
+/// Releases a result along with its strings, NULL is ignored.
+///
+/// # Safety
+///
+/// `result` must come from `aoc_solve` and not have been freed already.
+#[no_mangle]
+pub unsafe extern "C" fn aoc_free(result: *mut AocResult) {
+    if !result.is_null() {
+        drop(Box::from_raw(result));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    unsafe fn call(day: u32, part: u32, input: &str) -> (Option<String>, Option<String>) {
+        let r = aoc_solve(day, part, input.as_ptr(), input.len());
+        let s = |p: *const c_char| (!p.is_null()).then(|| CStr::from_ptr(p).to_str().unwrap().to_string());
+        let out = (s(aoc_answer(r)), s(aoc_error(r)));
+        aoc_free(r);
+        out
+    }
+
+    #[test]
+    fn solve_test() {
+        unsafe {
+            assert_eq!((Some("142".to_string()), None), call(1, 1, "1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet"));
+            assert_eq!((None, Some("no such day: 26".to_string())), call(26, 1, ""));
+            let (answer, error) = call(6, 1, "garbage");
+            assert_eq!(None, answer);
+            assert!(error.unwrap().starts_with("parse error: "));
+            aoc_free(ptr::null_mut());
+        }
+    }
+}
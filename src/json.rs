@@ -0,0 +1,315 @@
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+// just enough json for the server, objects keep their key order
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(o) => o.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_usize(&self) -> Option<usize> {
+        self.as_f64().filter(|n| n.fract() == 0.0 && *n >= 0.0).map(|n| n as usize)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::String(s.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Json::String(s)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(n: usize) -> Self {
+        Json::Number(n as f64)
+    }
+}
+
+impl From<f64> for Json {
+    fn from(n: f64) -> Self {
+        Json::Number(n)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(o: Option<T>) -> Self {
+        o.map(Into::into).unwrap_or(Json::Null)
+    }
+}
+
+fn write_str(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    write!(f, "\"")
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{b}"),
+            Json::Number(n) if n.is_finite() => write!(f, "{n}"),
+            Json::Number(_) => write!(f, "null"),
+            Json::String(s) => write_str(f, s),
+            Json::Array(a) => {
+                write!(f, "[")?;
+                for (i, v) in a.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{v}")?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(o) => {
+                write!(f, "{{")?;
+                for (i, (k, v)) in o.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_str(f, k)?;
+                    write!(f, ":{v}")?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+// arrays and objects are parsed recursively, so nesting has to stop somewhere
+// short of the stack running out
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn skip_ws(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        match self.chars.next() {
+            Some(x) if x == c => Ok(()),
+            Some(x) => Err(format!("expected '{c}', found '{x}'")),
+            None => Err(format!("expected '{c}', found end of input")),
+        }
+    }
+
+    fn keyword(&mut self, word: &str, v: Json) -> Result<Json, String> {
+        for c in word.chars() {
+            self.expect(c)?;
+        }
+        Ok(v)
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_ws();
+        match self.chars.peek() {
+            None => Err("unexpected end of input".to_string()),
+            Some('n') => self.keyword("null", Json::Null),
+            Some('t') => self.keyword("true", Json::Bool(true)),
+            Some('f') => self.keyword("false", Json::Bool(false)),
+            Some('"') => self.string().map(Json::String),
+            Some('[') => self.nested(Self::array),
+            Some('{') => self.nested(Self::object),
+            Some(c) if *c == '-' || c.is_ascii_digit() => self.number(),
+            Some(c) => Err(format!("unexpected '{c}'")),
+        }
+    }
+
+    fn nested(&mut self, f: fn(&mut Self) -> Result<Json, String>) -> Result<Json, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(format!("nested deeper than {MAX_DEPTH}"));
+        }
+        self.depth += 1;
+        let v = f(self);
+        self.depth -= 1;
+        v
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let mut s = String::new();
+        while let Some(c) = self.chars.next_if(|c| c.is_ascii_digit() || "+-.eE".contains(*c)) {
+            s.push(c);
+        }
+        s.parse().map(Json::Number).map_err(|_| format!("bad number '{s}'"))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.chars.next() {
+                None => return Err("unterminated string".to_string()),
+                Some('"') => return Ok(s),
+                Some('\\') => match self.chars.next() {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('/') => s.push('/'),
+                    Some('n') => s.push('\n'),
+                    Some('r') => s.push('\r'),
+                    Some('t') => s.push('\t'),
+                    Some('b') => s.push('\u{8}'),
+                    Some('f') => s.push('\u{c}'),
+                    Some('u') => {
+                        let hex: String = (0..4).filter_map(|_| self.chars.next()).collect();
+                        let code = u32::from_str_radix(&hex, 16).map_err(|_| format!("bad escape '\\u{hex}'"))?;
+                        // surrogate pairs are not worth it here
+                        s.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                    }
+                    Some(c) => return Err(format!("bad escape '\\{c}'")),
+                    None => return Err("unterminated string".to_string()),
+                },
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, String> {
+        self.expect('[')?;
+        let mut a = vec![];
+        self.skip_ws();
+        if self.chars.next_if_eq(&']').is_some() {
+            return Ok(Json::Array(a));
+        }
+        loop {
+            a.push(self.value()?);
+            self.skip_ws();
+            match self.chars.next() {
+                Some(',') => {}
+                Some(']') => return Ok(Json::Array(a)),
+                _ => return Err("expected ',' or ']'".to_string()),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, String> {
+        self.expect('{')?;
+        let mut o = vec![];
+        self.skip_ws();
+        if self.chars.next_if_eq(&'}').is_some() {
+            return Ok(Json::Object(o));
+        }
+        loop {
+            self.skip_ws();
+            let k = self.string()?;
+            self.skip_ws();
+            self.expect(':')?;
+            o.push((k, self.value()?));
+            self.skip_ws();
+            match self.chars.next() {
+                Some(',') => {}
+                Some('}') => return Ok(Json::Object(o)),
+                _ => return Err("expected ',' or '}'".to_string()),
+            }
+        }
+    }
+}
+
+pub fn parse(s: &str) -> Result<Json, String> {
+    let mut p = Parser {
+        chars: s.chars().peekable(),
+        depth: 0,
+    };
+    let v = p.value()?;
+    p.skip_ws();
+    match p.chars.next() {
+        None => Ok(v),
+        Some(c) => Err(format!("trailing '{c}'")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        let v = parse(r#" {"day": 1, "part": 2, "input": "a\nb \"c\" A", "params": {"x": -1.5e2}, "l": [true, false, null]} "#).unwrap();
+        assert_eq!(Some(1), v.get("day").and_then(Json::as_usize));
+        assert_eq!(Some("a\nb \"c\" A"), v.get("input").and_then(Json::as_str));
+        assert_eq!(Some(-150.0), v.get("params").and_then(|p| p.get("x")).and_then(Json::as_f64));
+        assert_eq!(Some(&Json::Array(vec![Json::Bool(true), Json::Bool(false), Json::Null])), v.get("l"));
+        assert_eq!(None, v.get("nope"));
+        assert_eq!(Ok(Json::Array(vec![])), parse("[]"));
+        assert_eq!(Ok(Json::Object(vec![])), parse(" { } "));
+    }
+
+    #[test]
+    fn error_test() {
+        assert!(parse("").is_err());
+        assert!(parse("{\"a\" 1}").is_err());
+        assert!(parse("[1, 2").is_err());
+        assert!(parse("\"abc").is_err());
+        assert!(parse("1 2").is_err());
+        assert!(parse("nul").is_err());
+    }
+
+    #[test]
+    fn depth_test() {
+        let nested = |n| "[".repeat(n) + &"]".repeat(n);
+        assert!(parse(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(Err("nested deeper than 128".to_string()), parse(&nested(MAX_DEPTH + 1)));
+        assert!(parse(&"[".repeat(200_000)).is_err());
+        assert!(parse(&"{\"a\":".repeat(200_000)).is_err());
+    }
+
+    #[test]
+    fn display_test() {
+        let v = Json::Object(vec![
+            ("a".to_string(), Json::from(1)),
+            ("b".to_string(), Json::from("x\"y\n")),
+            ("c".to_string(), Json::from(None::<usize>)),
+            ("d".to_string(), Json::Array(vec![Json::from(0.5), Json::Bool(true)])),
+        ]);
+        let s = v.to_string();
+        assert_eq!(r#"{"a":1,"b":"x\"y\n","c":null,"d":[0.5,true]}"#, s);
+        assert_eq!(Ok(v), parse(&s));
+    }
+}
@@ -0,0 +1,14 @@
+extern crate self as aoc2023;
+
+pub mod aho;
+pub mod alloc;
+pub mod coord;
+pub mod cycle;
+pub mod days;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod json;
+pub mod memo;
+pub mod runner;
+pub mod server;
+pub mod tui;
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub hits: usize,
+    pub misses: usize,
+    pub size: usize,
+    pub peak: usize,
+    pub evictions: usize,
+}
+
+impl Stats {
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "hits: {}, misses: {}, hit rate: {:.1}%, size: {}, peak: {}, evictions: {}",
+            self.hits,
+            self.misses,
+            self.hit_rate() * 100.0,
+            self.size,
+            self.peak,
+            self.evictions
+        )
+    }
+}
+
+// explicit memo table, meant to be owned by whoever drives the recursion
+// and passed down by `&mut`, so it can be cleared between independent inputs
+#[derive(Clone, Debug)]
+pub struct Memo<K, V> {
+    table: HashMap<K, V>,
+    capacity: Option<usize>,
+    stats: Stats,
+}
+
+impl<K: Hash + Eq, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Memo {
+            table: HashMap::new(),
+            capacity: None,
+            stats: Stats::default(),
+        }
+    }
+
+    // once the table holds `capacity` entries it is dropped wholesale,
+    // recursive solvers only pay for recomputation, never for wrong answers
+    pub fn with_capacity_limit(capacity: usize) -> Self {
+        Memo {
+            capacity: Some(capacity),
+            ..Self::new()
+        }
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        let v = self.table.get(key).cloned();
+        match v {
+            Some(_) => self.stats.hits += 1,
+            None => self.stats.misses += 1,
+        }
+        v
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> V {
+        if let Some(cap) = self.capacity {
+            if cap == 0 {
+                return value;
+            }
+            if self.table.len() >= cap && !self.table.contains_key(&key) {
+                self.stats.evictions += self.table.len();
+                self.table.clear();
+            }
+        }
+        self.table.insert(key, value.clone());
+        self.stats.size = self.table.len();
+        self.stats.peak = self.stats.peak.max(self.stats.size);
+        value
+    }
+
+    pub fn get_or_insert_with(&mut self, key: K, f: impl FnOnce() -> V) -> V {
+        match self.get(&key) {
+            Some(v) => v,
+            None => {
+                let v = f();
+                self.insert(key, v)
+            }
+        }
+    }
+
+    // forgets the entries but keeps counting, so stats cover the whole run
+    pub fn clear(&mut self) {
+        self.table.clear();
+        self.stats.size = 0;
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = Stats {
+            size: self.table.len(),
+            peak: self.table.len(),
+            ..Stats::default()
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hit_miss_test() {
+        let mut m: Memo<u32, u32> = Memo::new();
+        assert_eq!(None, m.get(&1));
+        m.insert(1, 10);
+        assert_eq!(Some(10), m.get(&1));
+        assert_eq!(Some(10), m.get(&1));
+        assert_eq!(20, m.get_or_insert_with(2, || 20));
+        assert_eq!(20, m.get_or_insert_with(2, || panic!("should be cached")));
+        let s = m.stats();
+        assert_eq!((3, 2, 2, 2), (s.hits, s.misses, s.size, s.peak));
+    }
+
+    #[test]
+    fn clear_test() {
+        let mut m: Memo<u32, u32> = Memo::new();
+        m.insert(1, 10);
+        m.insert(2, 20);
+        m.clear();
+        assert!(m.is_empty());
+        assert_eq!(None, m.get(&1));
+        let s = m.stats();
+        assert_eq!((0, 1, 0, 2), (s.hits, s.misses, s.size, s.peak));
+        m.reset_stats();
+        assert_eq!(Stats::default(), m.stats());
+    }
+
+    #[test]
+    fn capacity_test() {
+        let mut m: Memo<u32, u32> = Memo::with_capacity_limit(2);
+        m.insert(1, 10);
+        m.insert(2, 20);
+        m.insert(2, 21);
+        assert_eq!(2, m.len());
+        m.insert(3, 30);
+        assert_eq!(1, m.len());
+        assert_eq!(Some(30), m.get(&3));
+        assert_eq!(None, m.get(&1));
+        assert_eq!(2, m.stats().evictions);
+        assert_eq!(2, m.stats().peak);
+
+        let mut z: Memo<u32, u32> = Memo::with_capacity_limit(0);
+        assert_eq!(5, z.get_or_insert_with(1, || 5));
+        assert!(z.is_empty());
+    }
+
+    #[test]
+    fn recursion_test() {
+        fn fib(n: u64, m: &mut Memo<u64, u64>) -> u64 {
+            if n < 2 {
+                return n;
+            }
+            if let Some(v) = m.get(&n) {
+                return v;
+            }
+            let v = fib(n - 1, m) + fib(n - 2, m);
+            m.insert(n, v)
+        }
+        let mut m = Memo::new();
+        assert_eq!(12586269025, fib(50, &mut m));
+        assert_eq!(49, m.stats().misses);
+        assert_eq!(47, m.stats().hits);
+    }
+}
@@ -0,0 +1,245 @@
+use crate::alloc::{self, human_bytes, AllocStats};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{read_to_string, File};
+use std::io::{self, BufRead, BufReader};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Debug)]
+pub struct Step {
+    pub label: String,
+    pub answer: Option<String>,
+    pub time: Duration,
+    pub alloc: Option<AllocStats>,
+}
+
+// collects what a day computed, and what it cost, one step at a time
+#[derive(Clone, Debug, Default)]
+pub struct Runner {
+    pub steps: Vec<Step>,
+    pub notes: Vec<(String, String)>,
+    // knobs a day reads through `param`, e.g. the bounds for an example input
+    pub params: HashMap<String, String>,
+    // when set, the other parts are skipped
+    pub only: Option<usize>,
+    // label of the step in progress, left behind if it panics
+    pub running: Option<String>,
+    // grids a day can show off, only collected when set
+    pub frames: Option<Vec<Frame>>,
+    // the file the input is in, when there is one
+    pub path: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub label: String,
+    pub rows: Vec<String>,
+}
+
+impl Runner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn measure<R>(&mut self, label: String, f: impl FnOnce() -> R) -> (R, usize) {
+        self.running = Some(label.clone());
+        let start = Instant::now();
+        let (r, alloc) = alloc::measure(f);
+        let time = start.elapsed();
+        self.running = None;
+        self.steps.push(Step {
+            label,
+            answer: None,
+            time,
+            alloc,
+        });
+        (r, self.steps.len() - 1)
+    }
+
+    pub fn parse<R>(&mut self, f: impl FnOnce() -> R) -> R {
+        self.step("parse", f)
+    }
+
+    // for preprocessing shared by both parts that isn't really parsing
+    pub fn step<R>(&mut self, label: &str, f: impl FnOnce() -> R) -> R {
+        self.measure(label.to_string(), f).0
+    }
+
+    pub fn part<R: fmt::Display>(&mut self, n: usize, f: impl FnOnce() -> R) {
+        if self.only.is_some_and(|o| o != n) {
+            return;
+        }
+        let (r, i) = self.measure(format!("part {n}"), f);
+        self.steps[i].answer = Some(r.to_string());
+    }
+
+    pub fn param<T: FromStr>(&self, name: &str, default: T) -> T {
+        match self.params.get(name) {
+            Some(v) => v.parse().unwrap_or_else(|_| panic!("bad value for param {name}: {v}")),
+            None => default,
+        }
+    }
+
+    // `f` only gets called when someone is watching
+    pub fn frame(&mut self, label: &str, f: impl FnOnce() -> Vec<String>) {
+        if let Some(frames) = &mut self.frames {
+            frames.push(Frame {
+                label: label.to_string(),
+                rows: f(),
+            });
+        }
+    }
+
+    pub fn note(&mut self, label: &str, v: impl fmt::Display) {
+        self.notes.push((label.to_string(), v.to_string()));
+    }
+
+    // the input from the file when there is one, so a day can go through it a line
+    // at a time without ever holding all of it
+    pub fn reader<'a>(&self, input: &'a str) -> io::Result<Box<dyn BufRead + 'a>> {
+        match &self.path {
+            Some(p) => Ok(Box::new(BufReader::new(File::open(p)?))),
+            None => Ok(Box::new(input.as_bytes())),
+        }
+    }
+
+    pub fn answer(&self, n: usize) -> Option<&str> {
+        let label = format!("part {n}");
+        self.steps.iter().find(|s| s.label == label)?.answer.as_deref()
+    }
+
+    pub fn total(&self) -> Duration {
+        self.steps.iter().map(|s| s.time).sum()
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {:.3?}", self.label, self.time)?;
+        if let Some(a) = self.alloc {
+            write!(
+                f,
+                ", {} allocations, {} allocated, {} peak",
+                a.allocations,
+                human_bytes(a.bytes),
+                human_bytes(a.peak)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Runner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for s in &self.steps {
+            if let Some(a) = &s.answer {
+                writeln!(f, "{}: {}", s.label, a)?;
+            }
+        }
+        for (k, v) in &self.notes {
+            writeln!(f, "{k}: {v}")?;
+        }
+        writeln!(f, "--")?;
+        for s in &self.steps {
+            writeln!(f, "{s}")?;
+        }
+        Ok(())
+    }
+}
+
+// what every day's `main` boils down to
+pub fn main(f: &str, run: fn(&str, &mut Runner)) {
+    main_with(f, HashMap::new(), run);
+}
+
+// same, for a day whose binary takes some params of its own
+pub fn main_with(f: &str, params: HashMap<String, String>, run: fn(&str, &mut Runner)) {
+    let mut r = Runner {
+        params,
+        path: Some(f.to_string()),
+        ..Runner::new()
+    };
+    // a day that streams goes through `reader` for the file instead
+    let input = if r.param("stream", false) {
+        String::new()
+    } else {
+        read_to_string(f).unwrap()
+    };
+    run(&input, &mut r);
+    print!("{r}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(input: &str, r: &mut Runner) {
+        let numbers: Vec<usize> = r.parse(|| input.lines().map(|l| l.parse().unwrap()).collect());
+        r.part(1, || numbers.iter().sum::<usize>());
+        r.part(2, || numbers.iter().product::<usize>());
+        r.note("count", numbers.len());
+        let odd = r.step("odd", || numbers.iter().filter(|&x| x % 2 == 1).count());
+        r.part(3, || odd);
+    }
+
+    #[test]
+    fn steps_test() {
+        let mut r = Runner::new();
+        run("2\n3\n4", &mut r);
+        assert_eq!(
+            vec!["parse", "part 1", "part 2", "odd", "part 3"],
+            r.steps.iter().map(|s| s.label.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(None, r.steps[0].answer);
+        assert_eq!(Some("9"), r.answer(1));
+        assert_eq!(Some("24"), r.answer(2));
+        assert_eq!(Some("1"), r.answer(3));
+        assert_eq!(None, r.answer(4));
+        assert_eq!(alloc::enabled(), r.steps[0].alloc.is_some());
+        let out = r.to_string();
+        assert!(out.starts_with("part 1: 9\npart 2: 24\npart 3: 1\ncount: 3\n--\nparse: "));
+    }
+
+    #[test]
+    fn only_test() {
+        let mut r = Runner::new();
+        r.only = Some(2);
+        run("2\n3\n4", &mut r);
+        assert_eq!(
+            vec!["parse", "part 2", "odd"],
+            r.steps.iter().map(|s| s.label.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(None, r.answer(1));
+        assert_eq!(Some("24"), r.answer(2));
+        assert_eq!(None, r.running);
+    }
+
+    #[test]
+    fn frame_test() {
+        let mut r = Runner::new();
+        r.frame("a", || unreachable!());
+        assert_eq!(None, r.frames);
+        r.frames = Some(vec![]);
+        r.frame("b", || vec!["#.".to_string()]);
+        assert_eq!(Some(vec!["#.".to_string()]), r.frames.map(|f| f[0].rows.clone()));
+    }
+
+    #[test]
+    fn param_test() {
+        let mut r = Runner::new();
+        r.params.insert("steps".to_string(), "6".to_string());
+        assert_eq!(6, r.param("steps", 64));
+        assert_eq!(1.5, r.param("min", 1.5));
+    }
+
+    #[test]
+    fn reader_test() {
+        let mut r = Runner::new();
+        assert_eq!(2, r.reader("a\nb").unwrap().lines().count());
+        r.path = Some("inputs/4a".to_string());
+        assert_eq!(6, r.reader("").unwrap().lines().count());
+        r.path = Some("inputs/nothing".to_string());
+        assert!(r.reader("").is_err());
+    }
+}
@@ -0,0 +1,73 @@
+use aoc2023::alloc::{self, human_bytes};
+use aoc2023::days::{self, Day};
+use aoc2023::runner::Runner;
+use aoc2023::server::{self, Config};
+use aoc2023::tui;
+use std::env;
+use std::fs::read_to_string;
+use std::net::TcpListener;
+use std::time::Duration;
+
+fn run_day(d: &Day) -> Runner {
+    let input = read_to_string(d.input).unwrap();
+    let mut r = Runner::new();
+    (d.run)(&input, &mut r);
+    r
+}
+
+fn print_table(results: &[(usize, Runner)]) {
+    println!(
+        "{:>3} {:<8} {:>12} {:>12} {:>12} {:>10}  answer",
+        "day", "step", "time", "allocations", "allocated", "peak"
+    );
+    for (n, r) in results {
+        for s in &r.steps {
+            let (count, bytes, peak) = match s.alloc {
+                Some(a) => (a.allocations.to_string(), human_bytes(a.bytes), human_bytes(a.peak)),
+                None => ("-".to_string(), "-".to_string(), "-".to_string()),
+            };
+            let time = format!("{:.3?}", s.time);
+            let answer = s.answer.as_deref().unwrap_or("");
+            println!("{n:>3} {:<8} {time:>12} {count:>12} {bytes:>12} {peak:>10}  {answer}", s.label);
+        }
+    }
+}
+
+// runner serve [addr] [--workers n] [--queue n] [--timeout secs] [--stragglers n]
+fn serve(args: &[String]) {
+    let mut addr = "127.0.0.1:8023";
+    let mut config = Config::default();
+    let mut args = args.iter();
+    while let Some(a) = args.next() {
+        let mut value = || args.next().expect("missing value").parse::<usize>().unwrap();
+        match a.as_str() {
+            "--workers" => config.workers = value(),
+            "--queue" => config.queue = value(),
+            "--timeout" => config.timeout = Duration::from_secs(value() as u64),
+            "--stragglers" => config.stragglers = value(),
+            _ => addr = a,
+        }
+    }
+    let listener = TcpListener::bind(addr).unwrap();
+    println!("listening on {} with {} workers", listener.local_addr().unwrap(), config.workers);
+    server::serve(listener, config).unwrap();
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("serve") => return serve(&args[1..]),
+        Some("tui") => return tui::main(),
+        _ => {}
+    }
+    let selected: Vec<usize> = args.iter().map(|x| x.parse().unwrap()).collect();
+    let results: Vec<(usize, Runner)> = days::DAYS
+        .iter()
+        .filter(|d| selected.is_empty() || selected.contains(&d.number))
+        .map(|d| (d.number, run_day(d)))
+        .collect();
+    print_table(&results);
+    if !alloc::enabled() {
+        println!("(build with --features alloc-stats for allocation counts)");
+    }
+}
@@ -0,0 +1,424 @@
+use crate::days::{self, Day};
+use crate::json::{self, Json};
+use crate::runner::Runner;
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, RecvTimeoutError, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub workers: usize,
+    // connections waiting for a worker, anything beyond that gets a 503
+    pub queue: usize,
+    pub timeout: Duration,
+    // timed out solvers left running in the background, past that solves get a 503
+    pub stragglers: usize,
+    pub max_body: usize,
+    // request line and headers together
+    pub max_header: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            workers: thread::available_parallelism().map_or(4, |n| n.get()),
+            queue: 64,
+            timeout: Duration::from_secs(30),
+            stragglers: 4,
+            max_body: 16 << 20,
+            max_header: 16 << 10,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Request {
+    pub day: usize,
+    pub part: usize,
+    pub input: String,
+    pub params: HashMap<String, String>,
+}
+
+pub fn request(body: &str) -> Result<Request, String> {
+    let v = json::parse(body)?;
+    let day = v.get("day").and_then(Json::as_usize).ok_or("day must be a number")?;
+    let part = v.get("part").and_then(Json::as_usize).ok_or("part must be a number")?;
+    let input = v.get("input").and_then(Json::as_str).ok_or("input must be a string")?.to_string();
+    let mut params = HashMap::new();
+    match v.get("params") {
+        None | Some(Json::Null) => {}
+        Some(Json::Object(o)) => {
+            for (k, v) in o {
+                // strings are passed on as they are, so "7" and 7 mean the same
+                let v = v.as_str().map_or_else(|| v.to_string(), str::to_string);
+                params.insert(k.clone(), v);
+            }
+        }
+        Some(_) => return Err("params must be an object".to_string()),
+    }
+    Ok(Request { day, part, input, params })
+}
+
+pub type Solved = (Runner, Result<String, days::Error>);
+
+// the solver gets a thread of its own so it can be given up on; on timeout
+// that thread is handed back, nothing can stop it short of finishing
+pub fn solve(day: &'static Day, req: Request, timeout: Duration) -> Result<Solved, JoinHandle<()>> {
+    let (tx, rx) = mpsc::channel();
+    let solver = thread::Builder::new()
+        .name(format!("day {}", req.day))
+        .spawn(move || {
+            let _ = tx.send(day.solve(req.part, &req.input, req.params));
+        })
+        .unwrap();
+    match rx.recv_timeout(timeout) {
+        Ok(solved) => Ok(solved),
+        Err(RecvTimeoutError::Timeout) => Err(solver),
+        Err(RecvTimeoutError::Disconnected) => unreachable!("solver exits without a result"),
+    }
+}
+
+fn timings(r: &Runner) -> Json {
+    let steps = r.steps.iter().map(|s| {
+        Json::Object(vec![
+            ("step".to_string(), Json::from(s.label.as_str())),
+            ("ms".to_string(), Json::from(s.time.as_secs_f64() * 1000.)),
+        ])
+    });
+    Json::Array(steps.collect())
+}
+
+fn error(e: impl Into<String>) -> Json {
+    Json::Object(vec![("error".to_string(), Json::from(e.into()))])
+}
+
+pub struct Reply {
+    pub status: u16,
+    pub body: Json,
+    // a timed out solver, still running when the client gets its answer
+    pub straggler: Option<JoinHandle<()>>,
+}
+
+impl Reply {
+    fn new(status: u16, body: Json) -> Self {
+        Reply {
+            status,
+            body,
+            straggler: None,
+        }
+    }
+}
+
+fn solve_reply(body: &str, timeout: Duration) -> Reply {
+    let req = match request(body) {
+        Ok(req) => req,
+        Err(e) => return Reply::new(400, error(format!("bad request: {e}"))),
+    };
+    let Some(day) = days::get(req.day) else {
+        return Reply::new(404, error(format!("no such day: {}", req.day)));
+    };
+    let (n, part) = (req.day, req.part);
+    let (status, answer, err, r) = match solve(day, req, timeout) {
+        Ok((r, Ok(a))) => (200, Json::from(a), Json::Null, r),
+        Ok((r, Err(days::Error::NoSuchPart(_)))) => (404, Json::Null, Json::from(format!("day {n} has no part {part}")), r),
+        Ok((r, Err(e))) => (422, Json::Null, Json::from(e.to_string()), r),
+        Err(solver) => {
+            let mut reply = Reply::new(504, error(format!("timed out after {:?}", timeout)));
+            reply.straggler = Some(solver);
+            return reply;
+        }
+    };
+    let body = Json::Object(vec![
+        ("day".to_string(), Json::from(n)),
+        ("part".to_string(), Json::from(part)),
+        ("answer".to_string(), answer),
+        ("error".to_string(), err),
+        ("timings".to_string(), timings(&r)),
+        ("total_ms".to_string(), Json::from(r.total().as_secs_f64() * 1000.)),
+    ]);
+    Reply::new(status, body)
+}
+
+pub fn route(method: &str, path: &str, body: &str, config: &Config) -> Reply {
+    match (method, path) {
+        ("POST", "/solve") => solve_reply(body, config.timeout),
+        ("GET", "/days") => Reply::new(200, Json::Array(days::DAYS.iter().map(|d| Json::from(d.number)).collect())),
+        (_, "/solve" | "/days") => Reply::new(405, error(format!("{method} not allowed on {path}"))),
+        _ => Reply::new(404, error(format!("nothing at {path}"))),
+    }
+}
+
+// reads a line, taking its length off of what is left for the headers
+fn read_line(stream: &mut impl BufRead, line: &mut String, left: &mut usize) -> Result<(), Reply> {
+    let n = Read::take(&mut *stream, *left as u64)
+        .read_line(line)
+        .map_err(|_| Reply::new(400, error("unreadable headers")))?;
+    *left -= n;
+    if *left == 0 && !line.ends_with('\n') {
+        return Err(Reply::new(431, error("headers too large")));
+    }
+    Ok(())
+}
+
+// method, path and body; the error is the reply to send instead
+fn read_request(stream: &mut impl BufRead, max_header: usize, max_body: usize) -> Result<(String, String, String), Reply> {
+    let bad = |e: &str| Reply::new(400, error(e));
+    let mut left = max_header;
+    let mut line = String::new();
+    read_line(stream, &mut line, &mut left)?;
+    let mut words = line.split_whitespace();
+    let (Some(method), Some(path)) = (words.next(), words.next()) else {
+        return Err(bad("malformed request line"));
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+    let mut length = 0;
+    loop {
+        line.clear();
+        read_line(stream, &mut line, &mut left)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((k, v)) = header.split_once(':') {
+            if k.eq_ignore_ascii_case("content-length") {
+                length = v.trim().parse().map_err(|_| bad("bad content-length"))?;
+            }
+        }
+    }
+    if length > max_body {
+        return Err(Reply::new(413, error(format!("body over {max_body} bytes"))));
+    }
+    let mut body = vec![0; length];
+    stream.read_exact(&mut body).map_err(|_| bad("truncated body"))?;
+    let body = String::from_utf8(body).map_err(|_| bad("body is not utf-8"))?;
+    Ok((method, path, body))
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        422 => "Unprocessable Entity",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "",
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: u16, body: &Json) -> io::Result<()> {
+    let body = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {status} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        reason(status),
+        body.len()
+    )?;
+    stream.flush()
+}
+
+type Stragglers = Arc<Mutex<Vec<JoinHandle<()>>>>;
+
+// forgets the ones that finished in the meantime
+fn stragglers_full(stragglers: &Stragglers, max: usize) -> bool {
+    let mut s = stragglers.lock().unwrap();
+    s.retain(|t| !t.is_finished());
+    s.len() >= max
+}
+
+fn handle(mut stream: TcpStream, config: &Config, stragglers: &Stragglers) {
+    // a client that never finishes its request shouldn't hold a worker forever
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(10)));
+    let reply = match read_request(&mut BufReader::new(&stream), config.max_header, config.max_body) {
+        // timed out solvers keep their cores busy, taking on more work would only slow everything down
+        Ok((_, path, _)) if path == "/solve" && stragglers_full(stragglers, config.stragglers) => {
+            Reply::new(503, error("too many timed out solvers still running"))
+        }
+        Ok((method, path, body)) => route(&method, &path, &body, config),
+        Err(reply) => reply,
+    };
+    let _ = respond(&mut stream, reply.status, &reply.body);
+    // the worker moves on, the solver is left to finish in the background
+    if let Some(solver) = reply.straggler {
+        stragglers.lock().unwrap().push(solver);
+    }
+}
+
+pub fn serve(listener: TcpListener, config: Config) -> io::Result<()> {
+    let (tx, rx) = mpsc::sync_channel::<TcpStream>(config.queue);
+    let rx = Arc::new(Mutex::new(rx));
+    let stragglers = Stragglers::default();
+    for i in 0..config.workers {
+        let rx = rx.clone();
+        let config = config.clone();
+        let stragglers = stragglers.clone();
+        thread::Builder::new().name(format!("worker {i}")).spawn(move || loop {
+            let stream = rx.lock().unwrap().recv();
+            match stream {
+                Ok(stream) => handle(stream, &config, &stragglers),
+                Err(_) => return,
+            }
+        })?;
+    }
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(TrySendError::Full(mut stream)) = tx.try_send(stream) {
+                    let _ = respond(&mut stream, 503, &error("too many requests"));
+                }
+            }
+            Err(e) => eprintln!("accept failed: {e}"),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    const DAY1: &str = "1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet";
+
+    fn body(day: usize, part: usize, input: &str) -> String {
+        Json::Object(vec![
+            ("day".to_string(), Json::from(day)),
+            ("part".to_string(), Json::from(part)),
+            ("input".to_string(), Json::from(input)),
+        ])
+        .to_string()
+    }
+
+    #[test]
+    fn request_test() {
+        let req = request(r#"{"day": 24, "part": 1, "input": "x", "params": {"min": 7, "max": "27"}}"#).unwrap();
+        assert_eq!((24, 1, "x"), (req.day, req.part, req.input.as_str()));
+        assert_eq!(Some("7"), req.params.get("min").map(String::as_str));
+        assert_eq!(Some("27"), req.params.get("max").map(String::as_str));
+        assert!(request(r#"{"day": "1", "part": 1, "input": ""}"#).is_err());
+        assert!(request(r#"{"day": 1, "input": ""}"#).is_err());
+        assert!(request(r#"{"day": 1, "part": 1, "input": "", "params": 3}"#).is_err());
+    }
+
+    #[test]
+    fn route_test() {
+        let config = Config::default();
+        let reply = route("POST", "/solve", &body(1, 1, DAY1), &config);
+        assert_eq!(200, reply.status);
+        assert_eq!(Some("142"), reply.body.get("answer").and_then(Json::as_str));
+        assert_eq!(Some(&Json::Null), reply.body.get("error"));
+        // part 2 is skipped, day 1 has no parse step
+        assert_eq!(Some(1), reply.body.get("timings").map(|t| t.to_string().matches("step").count()));
+
+        assert_eq!(404, route("POST", "/solve", &body(25, 1, DAY1), &config).status);
+        let example = std::fs::read_to_string("inputs/21a").unwrap();
+        assert_eq!(404, route("POST", "/solve", &body(21, 2, &example), &config).status);
+        assert_eq!(400, route("POST", "/solve", "{", &config).status);
+        assert_eq!(405, route("GET", "/solve", "", &config).status);
+        assert_eq!(404, route("GET", "/", "", &config).status);
+        match route("GET", "/days", "", &config).body {
+            Json::Array(days) => assert_eq!(24, days.len()),
+            b => panic!("expected a list of days, got {b}"),
+        }
+    }
+
+    #[test]
+    fn params_test() {
+        let example = std::fs::read_to_string("inputs/24a").unwrap();
+        let req = format!(
+            r#"{{"day": 24, "part": 1, "input": {}, "params": {{"min": 7, "max": 27}}}}"#,
+            Json::from(example)
+        );
+        let reply = route("POST", "/solve", &req, &Config::default());
+        assert_eq!(Some("2"), reply.body.get("answer").and_then(Json::as_str));
+    }
+
+    #[test]
+    fn parse_error_test() {
+        let reply = route("POST", "/solve", &body(6, 1, "garbage"), &Config::default());
+        assert_eq!(422, reply.status);
+        let e = reply.body.get("error").and_then(Json::as_str).unwrap();
+        assert!(e.starts_with("parse error: "), "{e}");
+    }
+
+    fn slow(_: &str, r: &mut Runner) {
+        r.part(1, || {
+            thread::sleep(Duration::from_millis(300));
+            1
+        });
+    }
+
+    static SLOW: Day = Day {
+        number: 0,
+        input: "",
+        run: slow,
+    };
+
+    #[test]
+    fn timeout_test() {
+        let req = request(&body(0, 1, "")).unwrap();
+        match solve(&SLOW, req.clone(), Duration::from_millis(10)) {
+            Err(solver) => solver.join().unwrap(),
+            Ok(r) => panic!("expected a timeout, got {r:?}"),
+        }
+        assert_eq!(Ok("1".to_string()), solve(&SLOW, req, Duration::from_secs(10)).unwrap().1);
+    }
+
+    #[test]
+    fn stragglers_test() {
+        let stragglers = Stragglers::default();
+        assert!(!stragglers_full(&stragglers, 1));
+        let req = request(&body(0, 1, "")).unwrap();
+        stragglers.lock().unwrap().push(solve(&SLOW, req, Duration::from_millis(10)).unwrap_err());
+        assert!(stragglers_full(&stragglers, 1));
+        assert!(!stragglers_full(&stragglers, 2));
+        thread::sleep(Duration::from_millis(500));
+        assert!(!stragglers_full(&stragglers, 1));
+        assert!(stragglers.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn header_test() {
+        let req = |s: &str, max_header| read_request(&mut s.as_bytes(), max_header, 100).map_err(|r| r.status);
+        let ok = "POST /solve HTTP/1.1\r\nContent-Length: 2\r\n\r\n{}";
+        assert_eq!(Ok(("POST".to_string(), "/solve".to_string(), "{}".to_string())), req(ok, 100));
+        assert_eq!(Ok(("POST".to_string(), "/solve".to_string(), "{}".to_string())), req(ok, ok.len() - 2));
+        assert_eq!(Err(431), req(ok, ok.len() - 3));
+        assert_eq!(Err(431), req(&format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(1000)), 100));
+        assert_eq!(Err(413), req("POST /solve HTTP/1.1\r\nContent-Length: 101\r\n\r\n", 100));
+    }
+
+    #[test]
+    fn serve_test() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let config = Config {
+            workers: 2,
+            ..Config::default()
+        };
+        thread::spawn(move || serve(listener, config));
+        let clients = (0..4).map(|_| {
+            thread::spawn(move || {
+                let mut s = TcpStream::connect(addr).unwrap();
+                let b = body(1, 2, "two1nine\neightwothree");
+                write!(s, "POST /solve HTTP/1.1\r\nHost: x\r\nContent-Length: {}\r\n\r\n{b}", b.len()).unwrap();
+                let mut out = String::new();
+                s.read_to_string(&mut out).unwrap();
+                out
+            })
+        });
+        for c in clients.collect::<Vec<_>>() {
+            let out = c.join().unwrap();
+            assert!(out.starts_with("HTTP/1.1 200 OK\r\n"), "{out}");
+            let (_, body) = out.split_once("\r\n\r\n").unwrap();
+            assert_eq!(Some("112"), json::parse(body).unwrap().get("answer").and_then(Json::as_str));
+        }
+    }
+}
@@ -0,0 +1,604 @@
+use crate::days::{self, Day};
+use crate::runner::{Frame, Runner};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::panic;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// one line per input: `14a 136 64`, with `-` for an answer nobody checked yet
+const EXPECTED: &str = "inputs/expected";
+const TICK: Duration = Duration::from_millis(400);
+
+type Answers = [Option<String>; 2];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Char(char),
+}
+
+// arrows come in as escape sequences, usually all in one read
+pub fn keys(bytes: &[u8]) -> Vec<Key> {
+    let mut keys = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        match &bytes[i..] {
+            [0x1b, b'[' | b'O', c, ..] => {
+                match c {
+                    b'A' => keys.push(Key::Up),
+                    b'B' => keys.push(Key::Down),
+                    b'C' => keys.push(Key::Right),
+                    b'D' => keys.push(Key::Left),
+                    _ => {}
+                }
+                i += 3;
+                continue;
+            }
+            [b'\r' | b'\n', ..] => keys.push(Key::Enter),
+            [c, ..] => keys.push(Key::Char(*c as char)),
+            [] => unreachable!(),
+        }
+        i += 1;
+    }
+    keys
+}
+
+// inputs sort by day first, so 2a comes before 10a
+fn input_key(name: &str) -> (usize, &str) {
+    let digits = name.chars().take_while(char::is_ascii_digit).count();
+    (name[..digits].parse().unwrap_or(usize::MAX), &name[digits..])
+}
+
+pub fn inputs_for(day: usize, names: &[String]) -> Vec<String> {
+    let mut inputs: Vec<String> = names
+        .iter()
+        .filter(|n| {
+            let (d, rest) = input_key(n);
+            d == day && !rest.is_empty()
+        })
+        .cloned()
+        .collect();
+    inputs.sort_by(|a, b| input_key(a).cmp(&input_key(b)));
+    inputs
+}
+
+pub fn parse_expected(s: &str) -> HashMap<String, Answers> {
+    let answer = |a: Option<&str>| a.filter(|a| *a != "-").map(str::to_string);
+    s.lines()
+        .filter_map(|l| {
+            let mut words = l.split_whitespace();
+            let name = words.next()?.to_string();
+            Some((name, [answer(words.next()), answer(words.next())]))
+        })
+        .collect()
+}
+
+pub fn format_expected(expected: &HashMap<String, Answers>) -> String {
+    let mut names: Vec<&String> = expected.keys().collect();
+    names.sort_by(|a, b| input_key(a).cmp(&input_key(b)));
+    let mut s = String::new();
+    for n in names {
+        let [a, b] = &expected[n];
+        writeln!(s, "{n} {} {}", a.as_deref().unwrap_or("-"), b.as_deref().unwrap_or("-")).unwrap();
+    }
+    s
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Unchecked,
+    Ok,
+    Mismatch,
+}
+
+// parts without an answer or without an expectation don't count either way
+pub fn verdict(answers: &Answers, expected: &Answers) -> Verdict {
+    let checked = answers.iter().zip(expected).filter_map(|(a, e)| Some((a.as_ref()?, e.as_ref()?)));
+    let mut verdict = Verdict::Unchecked;
+    for (a, e) in checked {
+        if a != e {
+            return Verdict::Mismatch;
+        }
+        verdict = Verdict::Ok;
+    }
+    verdict
+}
+
+fn is_grid(input: &str) -> bool {
+    let mut lines = input.lines();
+    let width = lines.next().map_or(0, str::len);
+    width > 1 && input.lines().count() > 1 && lines.all(|l| l.len() == width)
+}
+
+#[derive(Clone, Debug)]
+struct Outcome {
+    answers: Answers,
+    error: Option<String>,
+    time: Duration,
+    frames: Vec<Frame>,
+}
+
+fn run(day: &Day, input: &str) -> Outcome {
+    let text = match fs::read_to_string(format!("inputs/{input}")) {
+        Ok(text) => text,
+        Err(e) => {
+            return Outcome {
+                answers: [None, None],
+                error: Some(format!("can't read {input}: {e}")),
+                time: Duration::ZERO,
+                frames: vec![],
+            }
+        }
+    };
+    let mut r = Runner::new();
+    r.frames = Some(vec![]);
+    let res = day.try_run(&text, &mut r);
+    let mut frames = r.frames.take().unwrap();
+    if frames.is_empty() && is_grid(&text) {
+        frames.push(Frame {
+            label: "input".to_string(),
+            rows: text.lines().map(str::to_string).collect(),
+        });
+    }
+    Outcome {
+        answers: [1, 2].map(|n| r.answer(n).map(str::to_string)),
+        error: res.err().map(|e| e.to_string()),
+        time: r.total(),
+        frames,
+    }
+}
+
+enum State {
+    Idle,
+    Queued,
+    Running(Instant),
+    Done(Outcome),
+}
+
+struct Entry {
+    day: &'static Day,
+    inputs: Vec<String>,
+    input: usize,
+    state: State,
+}
+
+impl Entry {
+    fn input(&self) -> &str {
+        self.inputs.get(self.input).map_or("-", String::as_str)
+    }
+}
+
+enum View {
+    List,
+    Visual { frame: usize, playing: bool },
+}
+
+enum Event {
+    Key(Key),
+    Started(usize),
+    Done(usize, String, Outcome),
+    Tick,
+}
+
+struct App {
+    entries: Vec<Entry>,
+    selected: usize,
+    expected: HashMap<String, Answers>,
+    view: View,
+    message: String,
+    size: (usize, usize),
+    jobs: Sender<(usize, &'static Day, String)>,
+}
+
+fn stty(args: &[&str]) -> Option<String> {
+    let out = Command::new("stty").args(args).stdin(Stdio::inherit()).output().ok()?;
+    out.status.success().then(|| String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+// rows and columns
+fn terminal_size() -> (usize, usize) {
+    let size = stty(&["size"]).and_then(|s| {
+        let (r, c) = s.split_once(' ')?;
+        Some((r.parse().ok()?, c.parse().ok()?))
+    });
+    // some terminals don't know their size and say 0 0
+    size.filter(|&(r, c)| r > 2 && c > 0).unwrap_or((40, 120))
+}
+
+// raw-ish keyboard and the alternate screen, undone on drop even when panicking
+struct Terminal {
+    saved: String,
+}
+
+impl Terminal {
+    fn enter() -> Option<Self> {
+        let saved = stty(&["-g"])?;
+        stty(&["-icanon", "-echo", "min", "1"])?;
+        print!("\x1b[?1049h\x1b[?25l");
+        Some(Terminal { saved })
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        print!("\x1b[?25h\x1b[?1049l");
+        let _ = io::stdout().flush();
+        stty(&[&self.saved]);
+    }
+}
+
+fn truncate(s: &str, width: usize) -> String {
+    if s.chars().count() <= width {
+        s.to_string()
+    } else if width == 0 {
+        String::new()
+    } else {
+        s.chars().take(width - 1).chain(Some('…')).collect()
+    }
+}
+
+fn paint(c: char) -> &'static str {
+    match c {
+        '.' => "\x1b[2m",
+        '#' => "\x1b[33m",
+        'O' => "\x1b[36m",
+        'S' => "\x1b[1;31m",
+        '0'..='9' => "\x1b[35m",
+        '│' | '─' | '└' | '┌' | '┐' | '┘' | '|' | '-' | '/' | '\\' => "\x1b[32m",
+        _ => "\x1b[0m",
+    }
+}
+
+impl App {
+    fn entry(&self) -> &Entry {
+        &self.entries[self.selected]
+    }
+
+    fn queue(&mut self, i: usize) {
+        let e = &mut self.entries[i];
+        if matches!(e.state, State::Queued | State::Running(_)) {
+            return;
+        }
+        e.state = State::Queued;
+        self.jobs.send((i, e.day, e.input().to_string())).unwrap();
+    }
+
+    fn pin(&mut self) {
+        let e = self.entry();
+        let State::Done(o) = &e.state else {
+            self.message = "nothing to pin yet".to_string();
+            return;
+        };
+        let name = e.input().to_string();
+        self.expected.insert(name.clone(), o.answers.clone());
+        self.message = match fs::write(EXPECTED, format_expected(&self.expected)) {
+            Ok(()) => format!("pinned answers for {name}"),
+            Err(e) => format!("can't write {EXPECTED}: {e}"),
+        };
+    }
+
+    fn frames(&self) -> &[Frame] {
+        match &self.entry().state {
+            State::Done(o) => &o.frames,
+            _ => &[],
+        }
+    }
+
+    // false once we're done
+    fn key(&mut self, k: Key) -> bool {
+        self.message.clear();
+        let n = self.frames().len().max(1);
+        match &mut self.view {
+            View::Visual { frame, playing } => match k {
+                Key::Char('q' | 'v' | '\x1b') => self.view = View::List,
+                Key::Char(' ') => *playing = !*playing,
+                Key::Right => (*frame, *playing) = ((*frame + 1) % n, false),
+                Key::Left => (*frame, *playing) = ((*frame + n - 1) % n, false),
+                _ => {}
+            },
+            View::List => match k {
+                Key::Char('q') => return false,
+                Key::Up | Key::Char('k') => self.selected = (self.selected + self.entries.len() - 1) % self.entries.len(),
+                Key::Down | Key::Char('j') => self.selected = (self.selected + 1) % self.entries.len(),
+                Key::Left | Key::Right | Key::Char('h' | 'l') => {
+                    let e = &mut self.entries[self.selected];
+                    if matches!(e.state, State::Queued | State::Running(_)) {
+                        self.message = "wait for the run to finish".to_string();
+                    } else if !e.inputs.is_empty() {
+                        let n = e.inputs.len();
+                        e.input = if matches!(k, Key::Left | Key::Char('h')) {
+                            (e.input + n - 1) % n
+                        } else {
+                            (e.input + 1) % n
+                        };
+                        e.state = State::Idle;
+                    }
+                }
+                Key::Enter | Key::Char('r') => self.queue(self.selected),
+                Key::Char('a') => (0..self.entries.len()).for_each(|i| self.queue(i)),
+                Key::Char('p') => self.pin(),
+                Key::Char('v') => {
+                    if self.frames().is_empty() {
+                        self.message = "no visual, run a grid day first".to_string();
+                    } else {
+                        self.view = View::Visual { frame: 0, playing: true };
+                    }
+                }
+                _ => {}
+            },
+        }
+        true
+    }
+
+    fn event(&mut self, e: Event) -> bool {
+        match e {
+            Event::Key(k) => {
+                self.size = terminal_size();
+                return self.key(k);
+            }
+            Event::Started(i) => self.entries[i].state = State::Running(Instant::now()),
+            Event::Done(i, input, o) => {
+                // the input got switched while it ran
+                if self.entries[i].input() == input {
+                    self.entries[i].state = State::Done(o);
+                }
+            }
+            Event::Tick => {
+                let n = self.frames().len().max(1);
+                if let View::Visual { frame, playing: true } = &mut self.view {
+                    *frame = (*frame + 1) % n;
+                }
+            }
+        }
+        true
+    }
+
+    fn list(&self, out: &mut String) {
+        let w = 16;
+        writeln!(
+            out,
+            "\x1b[1m day  input  status      {:w$}  {:w$}  {:w$}  {:w$}  {:>10}\x1b[0m",
+            "part 1", "expected", "part 2", "expected", "time"
+        )
+        .unwrap();
+        for (i, e) in self.entries.iter().enumerate() {
+            let expected = self.expected.get(e.input()).cloned().unwrap_or_default();
+            let (status, answers, time) = match &e.state {
+                State::Idle => ("\x1b[2m-          ".to_string(), [None, None], String::new()),
+                State::Queued => ("\x1b[2mqueued     ".to_string(), [None, None], String::new()),
+                State::Running(t) => (format!("\x1b[33mrunning {:>2}s", t.elapsed().as_secs()), [None, None], String::new()),
+                State::Done(o) => {
+                    let status = match (&o.error, verdict(&o.answers, &expected)) {
+                        (Some(_), _) => "\x1b[31merror      ",
+                        (None, Verdict::Mismatch) => "\x1b[1;31mMISMATCH   ",
+                        (None, Verdict::Ok) => "\x1b[32mok         ",
+                        (None, Verdict::Unchecked) => "done       ",
+                    };
+                    (status.to_string(), o.answers.clone(), format!("{:.1?}", o.time))
+                }
+            };
+            let cell = |a: &Option<String>| truncate(a.as_deref().unwrap_or(""), w);
+            let marker = if i == self.selected { "\x1b[7m>" } else { " " };
+            writeln!(
+                out,
+                "{marker}{:>3}  {:<5}  {status}\x1b[0m {:w$}  \x1b[2m{:w$}\x1b[0m  {:w$}  \x1b[2m{:w$}\x1b[0m  {time:>10}",
+                e.day.number,
+                e.input(),
+                cell(&answers[0]),
+                cell(&expected[0]),
+                cell(&answers[1]),
+                cell(&expected[1]),
+            )
+            .unwrap();
+        }
+        writeln!(out).unwrap();
+        let e = self.entry();
+        if let State::Done(Outcome { error: Some(err), .. }) = &e.state {
+            writeln!(
+                out,
+                "\x1b[31mday {}: {}\x1b[0m",
+                e.day.number,
+                truncate(err, self.size.1.saturating_sub(10))
+            )
+            .unwrap();
+        }
+        writeln!(out, "{}", self.message).unwrap();
+        writeln!(
+            out,
+            "\x1b[2m↑↓ day  ←→ input  r rerun  a run all  p pin as expected  v visual  q quit\x1b[0m"
+        )
+        .unwrap();
+    }
+
+    fn visual(&self, out: &mut String, frame: usize, playing: bool) {
+        let e = self.entry();
+        let frames = self.frames();
+        let Some(f) = frames.get(frame) else { return };
+        let (rows, cols) = self.size;
+        let cropped = f.rows.len() > rows - 2 || f.rows.iter().any(|r| r.chars().count() > cols);
+        writeln!(
+            out,
+            "\x1b[1mday {} · {} · {} ({}/{})\x1b[0m{}  \x1b[2m{} ←→ step  q back\x1b[0m",
+            e.day.number,
+            e.input(),
+            f.label,
+            frame + 1,
+            frames.len(),
+            if cropped { " cropped" } else { "" },
+            if playing { "space pause" } else { "space play" },
+        )
+        .unwrap();
+        for row in f.rows.iter().take(rows - 2) {
+            let mut color = "";
+            for c in row.chars().take(cols) {
+                if paint(c) != color {
+                    color = paint(c);
+                    out.push_str(color);
+                }
+                out.push(c);
+            }
+            writeln!(out, "\x1b[0m").unwrap();
+        }
+    }
+
+    fn draw(&self) {
+        let mut out = String::new();
+        match self.view {
+            View::List => self.list(&mut out),
+            View::Visual { frame, playing } => self.visual(&mut out, frame, playing),
+        }
+        // overwrite in place instead of clearing, that flickers
+        let screen = out.replace('\n', "\x1b[K\n");
+        let mut stdout = io::stdout().lock();
+        let _ = write!(stdout, "\x1b[H{screen}\x1b[J");
+        let _ = stdout.flush();
+    }
+}
+
+pub fn main() {
+    let names: Vec<String> = match fs::read_dir("inputs") {
+        Ok(dir) => dir.filter_map(|e| e.ok()?.file_name().into_string().ok()).collect(),
+        Err(e) => return eprintln!("can't list inputs: {e}"),
+    };
+    let entries = days::DAYS
+        .iter()
+        .map(|day| {
+            let inputs = inputs_for(day.number, &names);
+            let input = inputs.iter().position(|i| day.input.ends_with(&format!("/{i}"))).unwrap_or(0);
+            Entry {
+                day,
+                inputs,
+                input,
+                state: State::Idle,
+            }
+        })
+        .collect();
+    let expected = fs::read_to_string(EXPECTED).map(|s| parse_expected(&s)).unwrap_or_default();
+
+    let (events, rx) = mpsc::channel();
+    let (jobs, queue) = mpsc::channel::<(usize, &'static Day, String)>();
+    let tx = events.clone();
+    thread::Builder::new()
+        .name("solver".to_string())
+        .spawn(move || {
+            for (i, day, input) in queue {
+                let _ = tx.send(Event::Started(i));
+                let _ = tx.send(Event::Done(i, input.clone(), run(day, &input)));
+            }
+        })
+        .unwrap();
+    let tx = events.clone();
+    thread::spawn(move || {
+        let mut buf = [0; 64];
+        while let Ok(n @ 1..) = io::stdin().read(&mut buf) {
+            for k in keys(&buf[..n]) {
+                let _ = tx.send(Event::Key(k));
+            }
+        }
+    });
+    thread::spawn(move || loop {
+        thread::sleep(TICK);
+        if events.send(Event::Tick).is_err() {
+            return;
+        }
+    });
+
+    let Some(_terminal) = Terminal::enter() else {
+        return eprintln!("the tui needs a terminal");
+    };
+    // a day failing on bad input is reported in the table, not all over the screen
+    let default: Arc<dyn Fn(&panic::PanicHookInfo) + Sync + Send> = Arc::from(panic::take_hook());
+    let hook = default.clone();
+    panic::set_hook(Box::new(move |info| {
+        if thread::current().name() != Some("solver") {
+            hook(info);
+        }
+    }));
+    let mut app = App {
+        entries,
+        selected: 0,
+        expected,
+        view: View::List,
+        message: String::new(),
+        size: terminal_size(),
+        jobs,
+    };
+    app.draw();
+    for e in rx {
+        if !app.event(e) {
+            break;
+        }
+        app.draw();
+    }
+    // whatever runs after the tui gets its panics reported again
+    panic::set_hook(Box::new(move |info| default(info)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_test() {
+        assert_eq!(
+            vec![Key::Up, Key::Char('r'), Key::Left, Key::Enter, Key::Down],
+            keys(b"\x1b[Ar\x1bOD\n\x1b[B")
+        );
+        assert_eq!(vec![Key::Char('q')], keys(b"\x1b[5~q").split_off(1));
+    }
+
+    #[test]
+    fn inputs_test() {
+        let names = ["10a", "1b", "1a", "expected", "1", "11b", "1c"].map(str::to_string);
+        assert_eq!(vec!["1a", "1b", "1c"], inputs_for(1, &names));
+        assert_eq!(vec!["10a"], inputs_for(10, &names));
+        assert!(inputs_for(2, &names).is_empty());
+    }
+
+    #[test]
+    fn expected_test() {
+        let expected = parse_expected("14a 136 64\n21a 16 -\n\n2a 8 2286\n");
+        assert_eq!([Some("16".to_string()), None], expected["21a"]);
+        assert_eq!("2a 8 2286\n14a 136 64\n21a 16 -\n", format_expected(&expected));
+    }
+
+    #[test]
+    fn verdict_test() {
+        let s = |x: &str| Some(x.to_string());
+        assert_eq!(Verdict::Ok, verdict(&[s("1"), s("2")], &[s("1"), s("2")]));
+        assert_eq!(Verdict::Mismatch, verdict(&[s("1"), s("3")], &[s("1"), s("2")]));
+        assert_eq!(Verdict::Ok, verdict(&[s("1"), None], &[s("1"), s("2")]));
+        assert_eq!(Verdict::Unchecked, verdict(&[s("1"), s("2")], &[None, None]));
+    }
+
+    #[test]
+    fn truncate_test() {
+        assert_eq!("abc", truncate("abc", 3));
+        assert_eq!("ab…", truncate("abcd", 3));
+        assert_eq!("…", truncate("abcd", 1));
+        assert_eq!("", truncate("abcd", 0));
+        assert_eq!("", truncate("", 0));
+    }
+
+    #[test]
+    fn visual_test() {
+        let o = run(days::get(14).unwrap(), "14a");
+        assert_eq!([Some("136".to_string()), Some("64".to_string())], o.answers);
+        assert_eq!(13, o.frames.len());
+        assert_eq!("spin 1, north", o.frames[1].label);
+        assert_eq!("OOOO.#.O..", o.frames[1].rows[0]);
+        // no frames of its own, but the input is a grid
+        assert_eq!("input", run(days::get(11).unwrap(), "11a").frames[0].label);
+        assert!(run(days::get(1).unwrap(), "1a").frames.is_empty());
+    }
+}
@@ -0,0 +1,53 @@
+#include <stdio.h>
+#include <string.h>
+
+#include "aoc2023.h"
+
+static int failures = 0;
+
+static void expect(const char *what, const char *got, const char *want) {
+    if (got == NULL || strncmp(got, want, strlen(want)) != 0) {
+        fprintf(stderr, "%s: got %s, want %s\n", what, got ? got : "NULL", want);
+        failures++;
+    }
+}
+
+static void expect_null(const char *what, const char *got) {
+    if (got != NULL) {
+        fprintf(stderr, "%s: got %s, want NULL\n", what, got);
+        failures++;
+    }
+}
+
+int main(void) {
+    const char *day1 = "two1nine\neightwothree\nabcone2threexyz\nxtwone3four\n"
+                       "4nineeightseven2\nzoneight234\n7pqrstsixteen";
+    AocResult *r = aoc_solve(1, 2, (const uint8_t *)day1, strlen(day1));
+    expect("day 1 part 2", aoc_answer(r), "281");
+    expect_null("day 1 part 2 error", aoc_error(r));
+    aoc_free(r);
+
+    const char *day6 = "Time:      7  15   30\nDistance:  9  40  200";
+    r = aoc_solve(6, 2, (const uint8_t *)day6, strlen(day6));
+    expect("day 6 part 2", aoc_answer(r), "71503");
+    aoc_free(r);
+
+    r = aoc_solve(6, 1, (const uint8_t *)"garbage", 7);
+    expect_null("bad input answer", aoc_answer(r));
+    expect("bad input error", aoc_error(r), "parse error: ");
+    aoc_free(r);
+
+    r = aoc_solve(0, 1, NULL, 0);
+    expect("unknown day", aoc_error(r), "no such day: 0");
+    aoc_free(r);
+
+    r = aoc_solve(1, 1, (const uint8_t *)"\xff", 1);
+    expect("bad utf-8", aoc_error(r), "input is not utf-8");
+    aoc_free(r);
+
+    aoc_free(NULL);
+    if (failures == 0) {
+        printf("ok\n");
+    }
+    return failures != 0;
+}
@@ -0,0 +1,36 @@
+// builds the cdylib the way the ffi feature documents it, which also fails when
+// include/aoc2023.h is stale, then compiles and runs tests/ffi.c against it
+#![cfg(unix)]
+
+use std::path::Path;
+use std::process::Command;
+
+fn run(cmd: &mut Command) -> String {
+    let out = cmd.output().unwrap();
+    let stdout = String::from_utf8_lossy(&out.stdout).to_string();
+    assert!(out.status.success(), "{cmd:?} failed\n{stdout}{}", String::from_utf8_lossy(&out.stderr));
+    stdout
+}
+
+#[test]
+fn c_harness() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // a target dir of its own, the outer cargo holds the lock on the usual one
+    let target = Path::new(env!("CARGO_TARGET_TMPDIR")).join("ffi");
+    run(Command::new(env!("CARGO"))
+        .current_dir(root)
+        .args(["rustc", "--lib", "--features", "ffi", "--crate-type", "cdylib", "--target-dir"])
+        .arg(&target));
+    let lib = target.join("debug");
+    let exe = target.join("ffi_test");
+    run(Command::new(std::env::var("CC").unwrap_or("cc".to_string()))
+        .arg(root.join("tests/ffi.c"))
+        .arg("-I")
+        .arg(root.join("include"))
+        .arg("-L")
+        .arg(&lib)
+        .args(["-laoc2023", "-o"])
+        .arg(&exe));
+    let out = run(Command::new(&exe).env("LD_LIBRARY_PATH", &lib).env("DYLD_LIBRARY_PATH", &lib));
+    assert_eq!("ok\n", out);
+}